@@ -0,0 +1,36 @@
+//! A bot that plays the builtin levels against the engine without a terminal.
+//!
+//! cargo run --example bot -- [seed]
+
+use rustivanders::board::DEFAULT_LIVES;
+use rustivanders::headless::{self, Input, InputSource};
+use rustivanders::level_file;
+use rustivanders::{Assets, Board, Direction, FrameBuffer};
+
+/// Sweeps the screen from edge to edge, firing all the time.
+struct Sweeper;
+
+impl InputSource for Sweeper {
+    fn inputs(&mut self, tick: u64, _: &Board) -> Vec<Input> {
+        let dir = if (tick / 300).is_multiple_of(2) {
+            Direction::Left
+        } else {
+            Direction::Right
+        };
+        vec![Input::Move(dir), Input::Fire]
+    }
+}
+
+fn main() {
+    let seed = std::env::args()
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(1);
+    let assets = Assets::builtin();
+    let levels = level_file::builtin_levels(&assets);
+    let mut board = Board::new(levels, assets, DEFAULT_LIVES, seed);
+
+    let outcome = headless::run(&mut board, 50_000, &mut Sweeper);
+    print!("{}", FrameBuffer::render(&board));
+    println!("{}", outcome);
+}
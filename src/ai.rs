@@ -1,8 +1,11 @@
 use crate::board::UpdateCommand;
 use crate::level::{SCREEN_HEIGHT, SCREEN_WIDTH};
-use crate::sprite::{Bullet, SpriteState};
-use crate::util::{Direction, Point};
+use crate::level_file::MarchDef;
+use crate::sprite::{Bullet, Sprite, SpriteState};
+use crate::util::{Direction, GameRng, Point};
 
+use rand::Rng;
+use std::cmp::Ordering;
 use tui::style::Color;
 
 pub struct BulletAi {
@@ -41,6 +44,14 @@ pub struct PlayerAi {
     pub ticks_to_fire: u32,
     pub do_fire: bool,
     pub do_move: Direction,
+    /// Column the middle of the ship heads for, movement stops there.
+    pub target_x: Option<i16>,
+}
+
+impl Default for PlayerAi {
+    fn default() -> Self {
+        PlayerAi::new()
+    }
 }
 
 impl PlayerAi {
@@ -49,6 +60,7 @@ impl PlayerAi {
             do_fire: false,
             do_move: Direction::None,
             ticks_to_fire: 15,
+            target_x: None,
         }
     }
 
@@ -66,10 +78,22 @@ impl PlayerAi {
         if self.ticks_to_fire > 0 {
             self.ticks_to_fire -= 1;
         }
+        if let Some(target) = self.target_x {
+            let x = sprite.fire_point().x;
+            self.do_move = match target.cmp(&x) {
+                Ordering::Less => Direction::Left,
+                Ordering::Greater => Direction::Right,
+                Ordering::Equal => {
+                    self.target_x = None;
+                    Direction::None
+                }
+            };
+        }
         match self.do_move {
             Direction::Left => {
                 if sprite.pos.x <= 1 {
                     self.do_move = Direction::None;
+                    self.target_x = None;
                 } else {
                     sprite.move_by(&Point::new(-1, 0));
                 }
@@ -77,6 +101,7 @@ impl PlayerAi {
             Direction::Right => {
                 if sprite.pos.x >= (SCREEN_WIDTH - 2) as i16 {
                     self.do_move = Direction::None;
+                    self.target_x = None;
                 } else {
                     sprite.move_by(&Point::new(1, 0));
                 }
@@ -88,6 +113,8 @@ impl PlayerAi {
 }
 
 pub struct InvanderAi {
+    /// Set when a `MarchAi` moves the alien, leaving only firing to this AI.
+    pub marching: bool,
     pub move_dir: Direction,
     pub ticks_to_spawn_bullet: u16,
     pub ticks_to_move: u16,
@@ -98,8 +125,15 @@ pub struct InvanderAi {
 }
 
 impl InvanderAi {
-    pub fn new(x_range: &(i16, i16), move_speed: u16, fire_rate: f64, bullet_speed: i32) -> Self {
+    pub fn new(
+        x_range: &(i16, i16),
+        move_speed: u16,
+        fire_rate: f64,
+        bullet_speed: i32,
+        rng: &mut GameRng,
+    ) -> Self {
         let mut alien = InvanderAi {
+            marching: false,
             x_range: *x_range,
             move_dir: Direction::Left,
             move_speed,
@@ -108,25 +142,28 @@ impl InvanderAi {
             ticks_to_spawn_bullet: 0,
             bullet_speed,
         };
-        alien.random_tick_to_spawn_bullet();
+        alien.random_tick_to_spawn_bullet(rng);
         alien
     }
 
-    fn random_tick_to_spawn_bullet(&mut self) {
-        self.ticks_to_spawn_bullet = (rand::random::<f64>() * 100.0 * self.fire_rate) as u16;
+    fn random_tick_to_spawn_bullet(&mut self, rng: &mut GameRng) {
+        // Counted down before it is checked, zero would wrap around
+        self.ticks_to_spawn_bullet = ((rng.gen::<f64>() * 100.0 * self.fire_rate) as u16).max(1);
     }
 }
 
 impl InvanderAi {
-    pub fn update(&mut self, sprite: &mut SpriteState) -> Vec<UpdateCommand> {
+    pub fn update(&mut self, sprite: &mut SpriteState, rng: &mut GameRng) -> Vec<UpdateCommand> {
         let mut commands: Vec<UpdateCommand> = Vec::new();
-        self.ticks_to_move -= 1;
+        if !self.marching {
+            self.ticks_to_move -= 1;
+        }
         if self.ticks_to_move == 0 {
             self.ticks_to_move = self.move_speed;
             match self.move_dir {
                 Direction::Left => {
                     sprite.move_by(&Point::new(-1, 0));
-                    if sprite.pos.x < self.x_range.0 as i16 {
+                    if sprite.pos.x < self.x_range.0 {
                         self.move_dir = Direction::Right;
                     }
                 }
@@ -141,7 +178,7 @@ impl InvanderAi {
         if self.fire_rate > 0.0 {
             self.ticks_to_spawn_bullet -= 1;
             if self.ticks_to_spawn_bullet == 0 {
-                self.random_tick_to_spawn_bullet();
+                self.random_tick_to_spawn_bullet(rng);
                 commands.push(UpdateCommand::SpawnBullet(sprite.fire(
                     Direction::Down,
                     Color::Red,
@@ -154,6 +191,76 @@ impl InvanderAi {
     }
 }
 
+/// Moves a whole swarm of aliens in lockstep: sideways until any member
+/// touches a screen edge, then one step down and back the other way. The
+/// swarm speeds up as its members are destroyed.
+pub struct MarchAi {
+    pub move_dir: Direction,
+    pub ticks_to_move: u16,
+    pub speed: u16,
+    pub min_speed: u16,
+    pub step_down: i16,
+    pub initial_count: usize,
+}
+
+impl MarchAi {
+    pub fn new(def: &MarchDef, initial_count: usize) -> Self {
+        MarchAi {
+            move_dir: Direction::Right,
+            ticks_to_move: def.speed,
+            speed: def.speed,
+            min_speed: def.min_speed,
+            step_down: def.step_down,
+            initial_count,
+        }
+    }
+
+    /// Ticks between steps for a swarm of `count` aliens, scaling linearly
+    /// from `speed` for the full swarm down to `min_speed`.
+    fn current_speed(&self, count: usize) -> u16 {
+        let scaled = self.speed as usize * count / self.initial_count.max(1);
+        scaled.clamp(self.min_speed as usize, self.speed as usize) as u16
+    }
+
+    pub fn update<'b, I>(&mut self, aliens: I)
+    where
+        I: Iterator<Item = &'b mut Box<dyn for<'a> Sprite<'a>>>,
+    {
+        let mut swarm = aliens.filter(|a| !a.is_boss()).collect::<Vec<_>>();
+        if swarm.is_empty() {
+            return;
+        }
+
+        self.ticks_to_move = self.ticks_to_move.saturating_sub(1);
+        if self.ticks_to_move > 0 {
+            return;
+        }
+        self.ticks_to_move = self.current_speed(swarm.len());
+
+        let left = swarm.iter().map(|a| a.state().pos.x).min().unwrap_or(0);
+        let right = swarm
+            .iter()
+            .map(|a| a.state().pos.x + a.state().width() - 1)
+            .max()
+            .unwrap_or(0);
+        let step = match self.move_dir {
+            Direction::Left if left <= 0 => {
+                self.move_dir = Direction::Right;
+                Point::new(0, -self.step_down)
+            }
+            Direction::Left => Point::new(-1, 0),
+            _ if right >= SCREEN_WIDTH as i16 - 1 => {
+                self.move_dir = Direction::Left;
+                Point::new(0, -self.step_down)
+            }
+            _ => Point::new(1, 0),
+        };
+        for alien in swarm.iter_mut() {
+            alien.state_mut().move_by(&step);
+        }
+    }
+}
+
 pub struct BossAi {
     pub move_dir: Direction,
     pub ticks_to_spawn_bullet: u16,
@@ -162,7 +269,10 @@ pub struct BossAi {
     pub y_range: (i16, i16),
     pub move_speed: u16,
     pub fire_rate: f64,
+    pub bullet_speed: i32,
+    /// Shots left in the current burst.
     pub bullet_count: u16,
+    pub burst_size: u16,
 }
 
 impl BossAi {
@@ -171,27 +281,32 @@ impl BossAi {
         y_range: &(i16, i16),
         move_speed: u16,
         fire_rate: f64,
+        bullet_speed: i32,
+        burst_size: u16,
+        rng: &mut GameRng,
     ) -> Self {
         let mut alien = BossAi {
-            bullet_count: 10,
+            bullet_count: burst_size,
+            burst_size,
             x_range: *x_range,
             y_range: *y_range,
             move_dir: Direction::Left,
             move_speed,
             ticks_to_move: move_speed,
             fire_rate,
+            bullet_speed,
             ticks_to_spawn_bullet: 0,
         };
-        alien.random_tick_to_spawn_bullet();
+        alien.random_tick_to_spawn_bullet(rng);
         alien
     }
 
-    fn random_tick_to_spawn_bullet(&mut self) {
-        self.ticks_to_spawn_bullet = (rand::random::<f64>() * 100.0 * self.fire_rate) as u16;
+    fn random_tick_to_spawn_bullet(&mut self, rng: &mut GameRng) {
+        self.ticks_to_spawn_bullet = (rng.gen::<f64>() * 100.0 * self.fire_rate) as u16;
     }
 
-    fn random_v_dir(&self) -> Direction {
-        if rand::random::<f64>() > 0.5 {
+    fn random_v_dir(&self, rng: &mut GameRng) -> Direction {
+        if rng.gen::<f64>() > 0.5 {
             return Direction::Up;
         }
         Direction::Down
@@ -199,17 +314,17 @@ impl BossAi {
 }
 
 impl BossAi {
-    fn calc_y_mod(&self, sprite: &SpriteState) -> i16 {
+    fn calc_y_mod(&self, sprite: &SpriteState, rng: &mut GameRng) -> i16 {
         if (sprite.pos.x == -35 && self.move_dir == Direction::Left)
             || (sprite.pos.x == SCREEN_WIDTH as i16 + 35 && self.move_dir == Direction::Right)
         {
-            return match self.random_v_dir() {
+            return match self.random_v_dir(rng) {
                 Direction::Down => {
-                    -((std::cmp::max(sprite.pos.y - self.y_range.0, 0) as f64
-                        * rand::random::<f64>()) as i16)
+                    -((std::cmp::max(sprite.pos.y - self.y_range.0, 0) as f64 * rng.gen::<f64>())
+                        as i16)
                 }
                 _ => {
-                    (std::cmp::max(self.y_range.1 - sprite.pos.y, 0) as f64 * rand::random::<f64>())
+                    (std::cmp::max(self.y_range.1 - sprite.pos.y, 0) as f64 * rng.gen::<f64>())
                         as i16
                 }
             };
@@ -218,20 +333,20 @@ impl BossAi {
         0
     }
 
-    pub fn update(&mut self, sprite: &mut SpriteState) -> Vec<UpdateCommand> {
+    pub fn update(&mut self, sprite: &mut SpriteState, rng: &mut GameRng) -> Vec<UpdateCommand> {
         let mut commands: Vec<UpdateCommand> = Vec::new();
         self.ticks_to_move -= 1;
         if self.ticks_to_move == 0 {
             self.ticks_to_move = self.move_speed;
             match self.move_dir {
                 Direction::Left => {
-                    sprite.move_by(&Point::new(-1, self.calc_y_mod(sprite)));
-                    if sprite.pos.x < self.x_range.0 as i16 {
+                    sprite.move_by(&Point::new(-1, self.calc_y_mod(sprite, rng)));
+                    if sprite.pos.x < self.x_range.0 {
                         self.move_dir = Direction::Right;
                     }
                 }
                 _ => {
-                    sprite.move_by(&Point::new(1, self.calc_y_mod(sprite)));
+                    sprite.move_by(&Point::new(1, self.calc_y_mod(sprite, rng)));
                     if sprite.pos.x > self.x_range.1 {
                         self.move_dir = Direction::Left;
                     }
@@ -246,13 +361,13 @@ impl BossAi {
                         -laser.y + sprite.pos.y,
                         Direction::Down,
                         Color::LightMagenta,
-                        1,
+                        self.bullet_speed,
                     )));
                 }
                 self.bullet_count -= 1;
             } else if self.bullet_count == 0 {
-                self.bullet_count = 10;
-                self.random_tick_to_spawn_bullet();
+                self.bullet_count = self.burst_size;
+                self.random_tick_to_spawn_bullet(rng);
             } else {
                 self.ticks_to_spawn_bullet -= 1;
             }
@@ -261,3 +376,329 @@ impl BossAi {
         commands
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::{Assets, Boss, Cell, Invander};
+
+    use rand::SeedableRng;
+
+    fn rng() -> GameRng {
+        GameRng::seed_from_u64(0)
+    }
+
+    fn ship(x: i16, y: i16, direction: Direction) -> SpriteState {
+        SpriteState {
+            hp: 1,
+            id: 7,
+            pos: Point::new(x, y),
+            direction,
+            cells: vec![vec![Cell::new('#', Color::White)]],
+        }
+    }
+
+    fn marcher(x: i16, y: i16, rng: &mut GameRng) -> Box<dyn for<'a> Sprite<'a>> {
+        let mut ai = InvanderAi::new(&(0, 90), 1, 0.0, 1, rng);
+        ai.marching = true;
+        Box::new(Invander::new(
+            x,
+            y,
+            1,
+            10,
+            ship(x, y, Direction::Down).cells,
+            ai,
+        ))
+    }
+
+    fn march(speed: u16, min_speed: u16, count: usize) -> MarchAi {
+        let def = MarchDef {
+            speed,
+            min_speed,
+            step_down: 2,
+        };
+        MarchAi::new(&def, count)
+    }
+
+    /// Positions of the aliens after each of `ticks` updates.
+    fn march_positions(
+        march: &mut MarchAi,
+        aliens: &mut [Box<dyn for<'a> Sprite<'a>>],
+        ticks: usize,
+    ) -> Vec<Vec<Point>> {
+        (0..ticks)
+            .map(|_| {
+                march.update(aliens.iter_mut());
+                aliens.iter().map(|alien| alien.state().pos).collect()
+            })
+            .collect()
+    }
+
+    fn spawned_bullets(commands: &[UpdateCommand]) -> Vec<Point> {
+        commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                UpdateCommand::SpawnBullet(bullet) => Some(bullet.state.pos),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn removed_bullets(commands: &[UpdateCommand]) -> Vec<u32> {
+        commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                UpdateCommand::RemoveBullet(id) => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn invander_reverses_at_the_edges_of_its_range() {
+        let mut rng = rng();
+        let mut ai = InvanderAi::new(&(5, 8), 1, 0.0, 1, &mut rng);
+        let mut sprite = ship(6, 20, Direction::Down);
+
+        let mut xs = Vec::new();
+        for _ in 0..10 {
+            ai.update(&mut sprite, &mut rng);
+            xs.push(sprite.pos.x);
+        }
+
+        assert_eq!(xs, vec![5, 4, 5, 6, 7, 8, 9, 8, 7, 6]);
+    }
+
+    #[test]
+    fn invander_moves_every_move_speed_ticks() {
+        let mut rng = rng();
+        let mut ai = InvanderAi::new(&(0, 90), 3, 0.0, 1, &mut rng);
+        let mut sprite = ship(50, 20, Direction::Down);
+
+        let xs = (0..6)
+            .map(|_| {
+                ai.update(&mut sprite, &mut rng);
+                sprite.pos.x
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(xs, vec![50, 50, 49, 49, 49, 48]);
+    }
+
+    #[test]
+    fn invander_fires_when_its_countdown_rolls_zero() {
+        let mut rng = rng();
+        // Rolls below one tick every time
+        let mut ai = InvanderAi::new(&(0, 90), 100, 0.001, 1, &mut rng);
+        let mut sprite = ship(50, 20, Direction::Down);
+
+        let commands = ai.update(&mut sprite, &mut rng);
+
+        assert_eq!(spawned_bullets(&commands).len(), 1);
+        assert_eq!(ai.ticks_to_spawn_bullet, 1);
+    }
+
+    #[test]
+    fn marching_invander_stays_put() {
+        let mut rng = rng();
+        let mut ai = InvanderAi::new(&(0, 90), 1, 0.0, 1, &mut rng);
+        ai.marching = true;
+        let mut sprite = ship(50, 20, Direction::Down);
+
+        for _ in 0..5 {
+            ai.update(&mut sprite, &mut rng);
+        }
+
+        assert_eq!(sprite.pos, Point::new(50, 20));
+    }
+
+    #[test]
+    fn swarm_steps_down_and_reverses_at_the_right_edge() {
+        let mut rng = rng();
+        let right = SCREEN_WIDTH as i16 - 1;
+        let mut aliens = vec![marcher(right - 2, 20, &mut rng), marcher(50, 22, &mut rng)];
+        let mut march = march(1, 1, aliens.len());
+
+        let positions = march_positions(&mut march, &mut aliens, 4);
+
+        let at = |dx: i16, y: i16| vec![Point::new(right + dx, y), Point::new(52 + dx, y + 2)];
+        assert_eq!(
+            positions,
+            vec![at(-1, 20), at(0, 20), at(0, 18), at(-1, 18)]
+        );
+        assert_eq!(march.move_dir, Direction::Left);
+    }
+
+    #[test]
+    fn swarm_steps_down_and_reverses_at_the_left_edge() {
+        let mut rng = rng();
+        let mut aliens = vec![marcher(2, 20, &mut rng)];
+        let mut march = march(1, 1, aliens.len());
+        march.move_dir = Direction::Left;
+
+        let positions = march_positions(&mut march, &mut aliens, 4);
+
+        let at = |x: i16, y: i16| vec![Point::new(x, y)];
+        assert_eq!(positions, vec![at(1, 20), at(0, 20), at(0, 18), at(1, 18)]);
+        assert_eq!(march.move_dir, Direction::Right);
+    }
+
+    #[test]
+    fn swarm_speeds_up_as_it_shrinks() {
+        let march = march(30, 4, 10);
+
+        let speeds = [10, 8, 5, 2, 1, 0].map(|count| march.current_speed(count));
+
+        assert_eq!(speeds, [30, 24, 15, 6, 4, 4]);
+    }
+
+    #[test]
+    fn swarm_waits_its_current_speed_between_steps() {
+        let mut rng = rng();
+        let mut aliens = vec![marcher(50, 20, &mut rng), marcher(60, 20, &mut rng)];
+        let mut march = march(10, 2, 4);
+
+        let xs = march_positions(&mut march, &mut aliens, 15)
+            .into_iter()
+            .map(|positions| positions[0].x)
+            .collect::<Vec<_>>();
+
+        // Starts at the full speed, half the swarm steps every 5 ticks
+        assert_eq!(&xs[8..], &[50, 51, 51, 51, 51, 51, 52]);
+        assert_eq!(march.ticks_to_move, 5);
+    }
+
+    #[test]
+    fn boss_is_left_out_of_the_swarm() {
+        let mut rng = rng();
+        let cells = Assets::builtin().cells("boss");
+        let ai = BossAi::new(&(-35, 129), &(15, 25), 1000, 0.0, 2, 10, &mut rng);
+        let mut aliens = vec![
+            marcher(50, 20, &mut rng),
+            Box::new(Boss::new(10, 25, 20, 500, cells, ai)) as Box<dyn for<'a> Sprite<'a>>,
+        ];
+        let mut march = march(1, 1, 1);
+
+        let positions = march_positions(&mut march, &mut aliens, 2);
+        assert_eq!(positions[1], vec![Point::new(52, 20), Point::new(10, 25)]);
+
+        aliens.remove(0);
+        let positions = march_positions(&mut march, &mut aliens, 2);
+        assert_eq!(positions[1], vec![Point::new(10, 25)]);
+        assert_eq!(march.ticks_to_move, 1);
+    }
+
+    #[test]
+    fn bullet_is_removed_after_leaving_the_top() {
+        let mut ai = BulletAi::new(1);
+        let mut sprite = ship(10, SCREEN_HEIGHT as i16 - 1, Direction::Up);
+
+        assert!(removed_bullets(&ai.update(&mut sprite)).is_empty());
+        assert_eq!(sprite.pos.y, SCREEN_HEIGHT as i16);
+        assert_eq!(removed_bullets(&ai.update(&mut sprite)), vec![7]);
+    }
+
+    #[test]
+    fn bullet_is_removed_after_leaving_the_bottom() {
+        let mut ai = BulletAi::new(1);
+        let mut sprite = ship(10, 1, Direction::Down);
+
+        assert!(removed_bullets(&ai.update(&mut sprite)).is_empty());
+        assert_eq!(removed_bullets(&ai.update(&mut sprite)), vec![7]);
+        assert_eq!(sprite.pos.y, -1);
+    }
+
+    #[test]
+    fn bullet_moves_once_per_speed_ticks() {
+        let mut ai = BulletAi::new(3);
+        let mut sprite = ship(10, 10, Direction::Down);
+
+        let ys = (0..6)
+            .map(|_| {
+                ai.update(&mut sprite);
+                sprite.pos.y
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(ys, vec![10, 10, 9, 9, 9, 8]);
+    }
+
+    #[test]
+    fn player_fire_waits_for_the_cooldown() {
+        let mut ai = PlayerAi::new();
+        let mut sprite = ship(47, 1, Direction::Up);
+
+        let mut fired_at = Vec::new();
+        for tick in 1..=40 {
+            ai.do_fire = true;
+            let commands = ai.update(&mut sprite);
+            let fired = commands
+                .iter()
+                .any(|cmd| matches!(cmd, UpdateCommand::SpawnPlayerBullet(_)));
+            if fired {
+                fired_at.push(tick);
+            }
+        }
+
+        assert_eq!(fired_at, vec![16, 31]);
+    }
+
+    #[test]
+    fn player_fire_request_is_kept_until_the_cooldown_ends() {
+        let mut ai = PlayerAi::new();
+        ai.ticks_to_fire = 3;
+        ai.do_fire = true;
+        let mut sprite = ship(47, 1, Direction::Up);
+
+        let fired = (0..4)
+            .map(|_| !ai.update(&mut sprite).is_empty())
+            .collect::<Vec<_>>();
+
+        assert_eq!(fired, vec![false, false, false, true]);
+        assert!(!ai.do_fire);
+        assert_eq!(ai.ticks_to_fire, 14);
+    }
+
+    #[test]
+    fn player_stops_at_the_target_column() {
+        let mut ai = PlayerAi::new();
+        ai.target_x = Some(50);
+        let mut sprite = ship(47, 1, Direction::Up);
+
+        let xs = (0..5)
+            .map(|_| {
+                ai.update(&mut sprite);
+                sprite.pos.x
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(xs, vec![48, 49, 50, 50, 50]);
+        assert_eq!(ai.target_x, None);
+        assert_eq!(ai.do_move, Direction::None);
+    }
+
+    #[test]
+    fn boss_bursts_from_every_laser() {
+        let mut rng = rng();
+        let cells = Assets::builtin().cells("boss");
+        let mut sprite = SpriteState {
+            hp: 20,
+            id: 1,
+            pos: Point::new(30, 25),
+            direction: Direction::Down,
+            cells,
+        };
+        // The 'V' cannons sit at both ends of the fifth row
+        let lasers = vec![Point::new(30, 21), Point::new(62, 21)];
+
+        let mut ai = BossAi::new(&(-35, 129), &(15, 25), 1000, 1.0, 2, 10, &mut rng);
+        ai.ticks_to_spawn_bullet = 0;
+        for _ in 0..10 {
+            assert_eq!(spawned_bullets(&ai.update(&mut sprite, &mut rng)), lasers);
+        }
+        assert_eq!(ai.bullet_count, 0);
+
+        assert!(spawned_bullets(&ai.update(&mut sprite, &mut rng)).is_empty());
+        assert_eq!(ai.bullet_count, 10);
+    }
+}
@@ -0,0 +1,275 @@
+use termion::event::Key;
+
+/// What a key does while a game is running.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Stop,
+    Fire,
+    NextLevel,
+    Pause,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Stop,
+        Action::Fire,
+        Action::NextLevel,
+        Action::Pause,
+        Action::Quit,
+    ];
+
+    /// Name used in config and replay files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Stop => "stop",
+            Action::Fire => "fire",
+            Action::NextLevel => "next_level",
+            Action::Pause => "pause",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Name shown on the bindings screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Stop => "Stop",
+            Action::Fire => "Fire",
+            Action::NextLevel => "Next level",
+            Action::Pause => "Pause",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// Sets of bindings to start a config from.
+pub const PRESETS: [&str; 3] = ["arrows", "wasd", "vim"];
+
+/// Keys mapped onto actions, any number of keys per action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings {
+    keys: Vec<(Key, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings::preset("arrows").unwrap()
+    }
+}
+
+impl Bindings {
+    pub fn empty() -> Self {
+        Bindings { keys: Vec::new() }
+    }
+
+    /// One of `PRESETS`. All of them keep the arrow keys working and quit
+    /// with Esc too.
+    pub fn preset(name: &str) -> Option<Self> {
+        let (left, right, stop, fire) = match name {
+            "arrows" => (None, None, None, None),
+            "wasd" => (Some('a'), Some('d'), Some('s'), Some('w')),
+            "vim" => (Some('h'), Some('l'), Some('j'), Some('k')),
+            _ => return None,
+        };
+        let mut bindings = Bindings::empty();
+        for (action, key) in [
+            (Action::MoveLeft, left),
+            (Action::MoveRight, right),
+            (Action::Stop, stop),
+            (Action::Fire, fire),
+        ] {
+            if let Some(c) = key {
+                bindings.bind(action, Key::Char(c));
+            }
+        }
+        bindings.bind(Action::MoveLeft, Key::Left);
+        bindings.bind(Action::MoveRight, Key::Right);
+        bindings.bind(Action::Stop, Key::Down);
+        bindings.bind(Action::Fire, Key::Char(' '));
+        bindings.bind(Action::NextLevel, Key::Char('n'));
+        bindings.bind(Action::Pause, Key::Char('p'));
+        bindings.bind(Action::Quit, Key::Char('q'));
+        bindings.bind(Action::Quit, Key::Esc);
+        Some(bindings)
+    }
+
+    /// Adds `key` to the keys of `action`, taking it away from any other
+    /// action.
+    pub fn bind(&mut self, action: Action, key: Key) {
+        self.keys.retain(|(bound, _)| *bound != key);
+        self.keys.push((key, action));
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.keys.retain(|(_, bound)| *bound != action);
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| *action)
+    }
+
+    /// Keys of `action` in the order they were bound.
+    pub fn keys(&self, action: Action) -> Vec<Key> {
+        self.keys
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Key, Action)> + '_ {
+        self.keys.iter().copied()
+    }
+
+    /// The first key of `action` quoted for prompts, e.g. `'p'`.
+    pub fn prompt(&self, action: Action) -> String {
+        match self.keys(action).first() {
+            Some(key) => format!("'{}'", key_name(*key)),
+            None => "nothing".to_string(),
+        }
+    }
+}
+
+/// Parses a key as written in config files: a single character, a name
+/// like `left`, `space` or `esc`, `f1` to `f12`, or `ctrl-c` and `alt-c`.
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(c));
+    }
+    let modified = |prefix: &str| {
+        let mut chars = name.strip_prefix(prefix)?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    if let Some(c) = modified("ctrl-") {
+        return Some(Key::Ctrl(c));
+    }
+    if let Some(c) = modified("alt-") {
+        return Some(Key::Alt(c));
+    }
+    let key = match name {
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "backspace" => Key::Backspace,
+        "esc" => Key::Esc,
+        "space" => Key::Char(' '),
+        "enter" => Key::Char('\n'),
+        "tab" => Key::Char('\t'),
+        _ => match name.strip_prefix('f')?.parse() {
+            Ok(n @ 1..=12) => Key::F(n),
+            _ => return None,
+        },
+    };
+    Some(key)
+}
+
+/// The name `parse_key` reads `key` from.
+pub fn key_name(key: Key) -> String {
+    let name = match key {
+        Key::Left => "left",
+        Key::Right => "right",
+        Key::Up => "up",
+        Key::Down => "down",
+        Key::Home => "home",
+        Key::End => "end",
+        Key::PageUp => "pageup",
+        Key::PageDown => "pagedown",
+        Key::Delete => "delete",
+        Key::Insert => "insert",
+        Key::Backspace => "backspace",
+        Key::Esc => "esc",
+        Key::Char(' ') => "space",
+        Key::Char('\n') => "enter",
+        Key::Char('\t') => "tab",
+        Key::Char(c) => return c.to_string(),
+        Key::Ctrl(c) => return format!("ctrl-{}", c),
+        Key::Alt(c) => return format!("alt-{}", c),
+        Key::F(n) => return format!("f{}", n),
+        _ => "?",
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_bind_every_action() {
+        for name in PRESETS {
+            let bindings = Bindings::preset(name).unwrap();
+            for action in Action::ALL {
+                assert!(!bindings.keys(action).is_empty(), "{} {:?}", name, action);
+            }
+        }
+    }
+
+    #[test]
+    fn wasd_keeps_the_arrows() {
+        let bindings = Bindings::preset("wasd").unwrap();
+
+        assert_eq!(bindings.action(Key::Char('a')), Some(Action::MoveLeft));
+        assert_eq!(bindings.action(Key::Left), Some(Action::MoveLeft));
+        assert_eq!(
+            bindings.keys(Action::Fire),
+            vec![Key::Char('w'), Key::Char(' ')]
+        );
+        assert_eq!(bindings.action(Key::Char('x')), None);
+    }
+
+    #[test]
+    fn a_key_has_one_action() {
+        let mut bindings = Bindings::default();
+
+        bindings.bind(Action::Fire, Key::Char('p'));
+
+        assert_eq!(bindings.action(Key::Char('p')), Some(Action::Fire));
+        assert!(bindings.keys(Action::Pause).is_empty());
+    }
+
+    #[test]
+    fn key_names_round_trip() {
+        let keys = [
+            Key::Char('x'),
+            Key::Char(' '),
+            Key::Char('\n'),
+            Key::Left,
+            Key::Esc,
+            Key::F(5),
+            Key::Ctrl('c'),
+            Key::Alt('-'),
+        ];
+        for key in keys {
+            assert_eq!(parse_key(&key_name(key)), Some(key));
+        }
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("ctrl-"), None);
+        assert_eq!(parse_key("nope"), None);
+    }
+}
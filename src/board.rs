@@ -1,10 +1,22 @@
-use crate::level::{Level, SpriteCategory, SCREEN_HEIGHT, SCREEN_WIDTH};
-use crate::renderer::Renderable;
-use crate::sprite::{Bullet, Player, Sprite};
-use crate::util::Direction;
+use crate::difficulty::Difficulty;
+use crate::level::{Level, SpriteCategory, SCREEN_WIDTH};
+use crate::level_file::LevelDef;
+use crate::sprite::{Assets, Bullet, Player, Sprite};
+use crate::surface::{Renderable, Surface};
+use crate::util::{Direction, GameRng, Point};
 
-use tui::style::Color;
-use tui::widgets::canvas::Context;
+use rand::SeedableRng;
+
+pub const DEFAULT_LIVES: u16 = 3;
+
+/// Ticks the player ignores hits for after respawning.
+const RESPAWN_INVULNERABLE_TICKS: u16 = 120;
+
+/// Awarded per level number when the last alien of a level is destroyed.
+const LEVEL_CLEAR_BONUS: u32 = 100;
+
+/// Awarded on top of the boss' own points.
+const BOSS_KILL_BONUS: u32 = 1000;
 
 pub enum UpdateCommand {
     SpawnBullet(Bullet),
@@ -16,20 +28,99 @@ pub enum UpdateCommand {
 pub struct Board {
     pub player: Player,
     pub level: Level,
+    pub levels: Vec<LevelDef>,
+    pub assets: Assets,
+    pub lives: u16,
+    pub score: u32,
     pub game_over: bool,
+    /// Seed the current game was started with, `rng` is reset to it on `new_game`.
+    pub seed: u64,
+    /// Level every new game starts at, counted from 1.
+    pub start_level: u16,
+    /// Scales the aliens of every level loaded from now on.
+    pub difficulty: Difficulty,
+    pub rng: GameRng,
 }
 
 impl Board {
-    pub fn new() -> Self {
+    pub fn new(levels: Vec<LevelDef>, assets: Assets, lives: u16, seed: u64) -> Self {
+        Board::starting_at(levels, assets, lives, seed, 1)
+    }
+
+    /// A board whose games start at level `start_level` instead of the first.
+    pub fn starting_at(
+        levels: Vec<LevelDef>,
+        assets: Assets,
+        lives: u16,
+        seed: u64,
+        start_level: u16,
+    ) -> Self {
+        assert!(!levels.is_empty(), "board needs at least one level");
+        assert!(
+            (1..=levels.len()).contains(&(start_level as usize)),
+            "no level {}",
+            start_level
+        );
+        let spawn = Board::player_spawn_point();
+        let mut rng = GameRng::seed_from_u64(seed);
+        let idx = start_level as usize - 1;
         Board {
             game_over: false,
-            player: Player::new((SCREEN_WIDTH / 2) as i16, 1),
-            level: Level::one(),
+            player: Player::new(spawn.x, spawn.y, lives, assets.cells("player")),
+            level: Level::from_def(
+                start_level,
+                &levels[idx],
+                Difficulty::Normal,
+                &assets,
+                &mut rng,
+            ),
+            levels,
+            assets,
+            lives,
+            score: 0,
+            seed,
+            start_level,
+            difficulty: Difficulty::Normal,
+            rng,
         }
     }
 
+    /// Plays at `difficulty` instead of `Normal`, starting the game over.
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self.new_game(self.seed);
+        self
+    }
+
+    fn player_spawn_point() -> Point {
+        Point::new((SCREEN_WIDTH / 2) as i16, 1)
+    }
+
+    /// Status line shown above the playfield.
+    pub fn hud(&self) -> String {
+        format!(
+            "Score: {:06}   Level: {}   Lives: {}   Seed: {}",
+            self.score, self.level.number, self.player.state.hp, self.seed
+        )
+    }
+
     pub fn move_player(&mut self, dir: Direction) {
         self.player.ai.do_move = dir;
+        self.player.ai.target_x = None;
+    }
+
+    /// Moves the player until the middle of the ship is at column `x`.
+    pub fn move_player_to(&mut self, x: i16) {
+        self.player.ai.target_x = Some(x);
+    }
+
+    /// Keeps the player moving in `dir` while it is held down. A respawn or a
+    /// new game drops the ship's move, which is picked back up here. A mouse
+    /// target is left alone.
+    pub fn hold_player(&mut self, dir: Direction) {
+        if self.player.ai.target_x.is_none() && self.player.ai.do_move != dir {
+            self.player.ai.do_move = dir;
+        }
     }
 
     pub fn player_fire(&mut self) {
@@ -41,19 +132,38 @@ impl Board {
         self.game_over = false;
     }
 
+    fn load_level(&mut self, number: u16) -> Level {
+        let idx = (number as usize - 1) % self.levels.len();
+        Level::from_def(
+            idx as u16 + 1,
+            &self.levels[idx],
+            self.difficulty,
+            &self.assets,
+            &mut self.rng,
+        )
+    }
+
     pub fn next_level(&mut self) {
         if self.level.is_finished() {
-            match self.level.number {
-                1 => self.reset_game_with_level(Level::two()),
-                2 => self.reset_game_with_level(Level::three()),
-                3 => self.reset_game_with_level(Level::four()),
-                _ => self.reset_game_with_level(Level::one()),
-            }
+            let level = self.load_level(self.level.number + 1);
+            self.reset_game_with_level(level);
         } else if self.game_over {
-            self.reset_game_with_level(Level::one());
+            self.new_game(self.seed);
         }
     }
 
+    /// Starts over from the start level with a fresh score and lives, using
+    /// `seed` for all randomness of the new game.
+    pub fn new_game(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = GameRng::seed_from_u64(seed);
+        let level = self.load_level(self.start_level);
+        self.reset_game_with_level(level);
+        self.score = 0;
+        self.player.state.hp = self.lives;
+        self.player.respawn(Board::player_spawn_point(), 0);
+    }
+
     pub fn update(&mut self) {
         if self.game_over || self.level.is_finished() {
             return;
@@ -61,14 +171,38 @@ impl Board {
 
         // Update sprites
         let mut after_update_commands = [
-            Board::update_sprites(self.level.bullets.values_mut()),
-            Board::update_sprites(self.level.player_bullets.values_mut()),
-            Board::update_sprites(self.level.aliens.values_mut()),
+            Board::update_sprites(self.level.bullets.values_mut(), &mut self.rng),
+            Board::update_sprites(self.level.player_bullets.values_mut(), &mut self.rng),
+            Board::update_sprites(self.level.aliens.values_mut(), &mut self.rng),
             self.player.ai.update(&mut self.player.state),
         ]
         .into_iter()
         .flat_map(|u| u.into_iter())
         .collect::<Vec<_>>();
+        if let Some(march) = &mut self.level.march {
+            march.update(self.level.aliens.values_mut());
+            if self.swarm_landed() {
+                self.game_over = true;
+            }
+        }
+
+        // Check collisions with bunkers, both sides erode them
+        for (bullet_id, bullet) in self
+            .level
+            .bullets
+            .iter()
+            .chain(self.level.player_bullets.iter())
+        {
+            for bunker in self.level.bunkers.values_mut() {
+                if bunker.erode(&bullet.state().pos) {
+                    after_update_commands.push(UpdateCommand::RemoveBullet(*bullet_id));
+                    break;
+                }
+            }
+        }
+        self.level
+            .bunkers
+            .retain(|_, bunker| !bunker.state().is_empty());
 
         // Check collisions with aliens
         for (bullet_id, bullet) in &self.level.player_bullets {
@@ -84,20 +218,51 @@ impl Board {
             }
         }
         self.execute_update_commands(after_update_commands);
+        if self.level.is_finished() {
+            self.score += LEVEL_CLEAR_BONUS * self.level.number as u32;
+        }
 
         // Check collistions with player
-        for bullet in self.level.bullets.values() {
-            if self.player.state.collides(&bullet.state().pos) {
-                self.game_over = true;
-            }
+        if self.player.is_invulnerable() {
+            self.player.invulnerable_ticks -= 1;
         }
+        let hit = self
+            .level
+            .bullets
+            .iter()
+            .find(|(_, bullet)| self.player.collides(&bullet.state().pos))
+            .map(|(id, _)| *id);
+        if let Some(bullet_id) = hit {
+            self.execute_update_commands(vec![UpdateCommand::RemoveBullet(bullet_id)]);
+            self.player_hit();
+        }
+    }
+
+    /// Whether a marching swarm has descended to the player's row. The boss
+    /// doesn't march and may fly low without landing.
+    fn swarm_landed(&self) -> bool {
+        self.level
+            .aliens
+            .values()
+            .filter(|alien| !alien.is_boss())
+            .any(|alien| alien.state().bottom() <= self.player.state.pos.y)
     }
 
-    fn update_sprites<'b, I>(sprites: I) -> Vec<UpdateCommand>
+    fn player_hit(&mut self) {
+        self.player.modify_hp(-1);
+        if self.player.state.hp == 0 {
+            self.game_over = true;
+        } else {
+            self.player
+                .respawn(Board::player_spawn_point(), RESPAWN_INVULNERABLE_TICKS);
+        }
+    }
+
+    fn update_sprites<'b, I>(sprites: I, rng: &mut GameRng) -> Vec<UpdateCommand>
     where
         I: Iterator<Item = &'b mut Box<dyn for<'a> Sprite<'a>>>,
     {
-        sprites.flat_map(|e| e.update()).collect::<Vec<_>>()
+        sprites.flat_map(|e| e.update(rng)).collect::<Vec<_>>()
     }
 
     fn execute_update_commands(&mut self, commands: Vec<UpdateCommand>) {
@@ -114,7 +279,12 @@ impl Board {
                     self.level.player_bullets.remove(&id);
                 }
                 UpdateCommand::RemoveInvander(id) => {
-                    self.level.aliens.remove(&id);
+                    if let Some(alien) = self.level.aliens.remove(&id) {
+                        self.score += alien.points();
+                        if alien.is_boss() {
+                            self.score += BOSS_KILL_BONUS;
+                        }
+                    }
                 }
             }
         }
@@ -122,39 +292,532 @@ impl Board {
 }
 
 impl Renderable for Board {
-    fn render(&self, ctx: &mut Context) {
-        self.player.state.render(ctx);
+    fn render(&self, surface: &mut dyn Surface) {
+        if self.player.is_visible() {
+            self.player.state.render(surface);
+        }
         for bullet in self.level.bullets.values() {
-            bullet.render(ctx);
+            bullet.render(surface);
         }
         for bullet in self.level.player_bullets.values() {
-            bullet.render(ctx);
+            bullet.render(surface);
         }
         for invander in self.level.aliens.values() {
-            invander.render(ctx);
-        }
-        if self.game_over {
-            let x = SCREEN_WIDTH as f64 / 2.0 - 5.0;
-            let y = SCREEN_HEIGHT as f64 / 2.5;
-            ctx.print(x, y, "GAME OVER", Color::Red);
-            ctx.print(
-                x - 9.0,
-                y - 1.0,
-                "Press 'n' to start new game",
-                Color::LightRed,
-            );
+            invander.render(surface);
+        }
+        for bunker in self.level.bunkers.values() {
+            bunker.render(surface);
         }
+    }
+}
 
-        if self.level.is_finished() {
-            let x = SCREEN_WIDTH as f64 / 2.0 - 4.0;
-            let y = SCREEN_HEIGHT as f64 / 2.0;
-            ctx.print(x, y, "You won!", Color::Green);
-            ctx.print(
-                x - 8.0,
-                y - 1.0,
-                "Press 'n' for next level",
-                Color::LightGreen,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{BossAi, InvanderAi, MarchAi};
+    use crate::level_file::{self, MarchDef};
+    use crate::sprite::{Boss, Bunker, Cell, Invander};
+
+    use tui::style::Color;
+
+    /// One alien of every kind, none of them moving or firing.
+    const SCORING_LEVEL: &str = "\
+[alien]
+kind = fighter
+pos = 10, 20
+x_range = 10, 10
+move_speed = 60000
+fire_rate = 0
+
+[alien]
+kind = tank
+pos = 25, 20
+x_range = 25, 25
+move_speed = 60000
+fire_rate = 0
+
+[alien]
+kind = assassin
+pos = 40, 20
+x_range = 40, 40
+move_speed = 60000
+fire_rate = 0
+
+[alien]
+kind = boss
+pos = 55, 25
+x_range = 55, 55
+move_speed = 60000
+fire_rate = 0
+";
+
+    fn board() -> Board {
+        let assets = Assets::builtin();
+        let levels = level_file::builtin_levels(&assets);
+        Board::new(levels, assets, DEFAULT_LIVES, 42)
+    }
+
+    fn clear_level(board: &mut Board) {
+        board.level.aliens.clear();
+    }
+
+    /// A board that starts at level `number` of copies of `SCORING_LEVEL`.
+    fn scoring_board(number: u16) -> Board {
+        let assets = Assets::builtin();
+        let def = level_file::parse_level("scoring.lvl", SCORING_LEVEL, &assets).unwrap();
+        let levels = vec![def; number as usize];
+        Board::starting_at(levels, assets, DEFAULT_LIVES, 42, number)
+    }
+
+    /// Shoots the alien `id` with standing bullets until it is destroyed,
+    /// returns the points scored for it.
+    fn destroy_alien(board: &mut Board, id: u32) -> u32 {
+        let score = board.score;
+        while let Some(alien) = board.level.aliens.get(&id) {
+            let state = alien.state();
+            let target = state
+                .cells
+                .iter()
+                .enumerate()
+                .find_map(|(dy, row)| {
+                    let dx = row.iter().position(|cell| cell.symbol != ' ')?;
+                    Some(state.pos + Point::new(dx as i16, -(dy as i16)))
+                })
+                .unwrap();
+            let bullet = Bullet::new(target.x, target.y, Direction::Up, Color::LightBlue, 1000);
+            board
+                .level
+                .add_sprite(Box::new(bullet), SpriteCategory::PlayerBullet);
+            board.update();
+        }
+        board.score - score
+    }
+
+    /// A board whose only alien sits in a corner without moving or firing.
+    fn quiet_board() -> Board {
+        let mut board = board();
+        let mut level = Level::new(1);
+        let ai = InvanderAi::new(&(0, 0), u16::MAX, 0.0, 1, &mut board.rng);
+        let cells = board.assets.cells("fighter");
+        level.add_sprite(
+            Box::new(Invander::new(0, 28, 1, 10, cells, ai)),
+            SpriteCategory::Alien,
+        );
+        board.level = level;
+        board
+    }
+
+    /// A quiet board with a swarm of one cell aliens at `members` that
+    /// steps every tick, and an idle boss at `boss`.
+    fn marching_board(members: &[(i16, i16)], boss: Option<(i16, i16)>) -> Board {
+        let mut board = board();
+        let mut level = Level::new(1);
+        for &(x, y) in members {
+            let mut ai = InvanderAi::new(&(0, 0), 1, 0.0, 1, &mut board.rng);
+            ai.marching = true;
+            let cells = vec![vec![Cell::new('#', Color::White)]];
+            level.add_sprite(
+                Box::new(Invander::new(x, y, 1, 10, cells, ai)),
+                SpriteCategory::Alien,
             );
         }
+        level.march = Some(MarchAi::new(
+            &MarchDef {
+                speed: 1,
+                min_speed: 1,
+                step_down: 1,
+            },
+            level.aliens.len(),
+        ));
+        if let Some((x, y)) = boss {
+            let ai = BossAi::new(&(0, 0), &(0, 0), u16::MAX, 0.0, 1, 1, &mut board.rng);
+            let cells = board.assets.cells("boss");
+            level.add_sprite(
+                Box::new(Boss::new(x, y, 1, 500, cells, ai)),
+                SpriteCategory::Alien,
+            );
+        }
+        board.level = level;
+        board
+    }
+
+    /// A quiet board with a bunker of two cells side by side at (20, 10).
+    fn bunker_board() -> Board {
+        let mut board = quiet_board();
+        let cells = vec![vec![Cell::new('#', Color::Green); 2]];
+        board
+            .level
+            .add_sprite(Box::new(Bunker::new(20, 10, cells)), SpriteCategory::Bunker);
+        board
+    }
+
+    fn add_bullet(board: &mut Board, x: i16, y: i16, category: SpriteCategory) {
+        let bullet = Bullet::new(x, y, Direction::Down, Color::Red, 1000);
+        board.level.add_sprite(Box::new(bullet), category);
+    }
+
+    fn bunker_cells(board: &Board) -> Vec<char> {
+        board
+            .level
+            .bunkers
+            .values()
+            .flat_map(|bunker| bunker.state().cells.concat())
+            .map(|cell| cell.symbol)
+            .collect()
+    }
+
+    /// Puts a standing alien bullet on the middle of the ship's lower row,
+    /// which it covers after a step to either side too.
+    fn shoot_player(board: &mut Board) {
+        let pos = board.player.state.pos;
+        let bullet = Bullet::new(pos.x + 1, pos.y - 1, Direction::Down, Color::Red, 1000);
+        board
+            .level
+            .add_sprite(Box::new(bullet), SpriteCategory::AlienBullet);
+    }
+
+    #[test]
+    fn next_level_cycles_through_all_levels() {
+        let mut board = board();
+        let count = board.levels.len() as u16;
+
+        let mut numbers = vec![board.level.number];
+        for _ in 0..count {
+            clear_level(&mut board);
+            board.next_level();
+            numbers.push(board.level.number);
+        }
+
+        let mut expected = (1..=count).collect::<Vec<_>>();
+        expected.push(1);
+        assert_eq!(numbers, expected);
+        assert!(!board.level.is_finished());
+    }
+
+    #[test]
+    fn next_level_keeps_an_unfinished_level() {
+        let mut board = board();
+        let aliens = board.level.aliens.len();
+
+        board.next_level();
+
+        assert_eq!(board.level.number, 1);
+        assert_eq!(board.level.aliens.len(), aliens);
+    }
+
+    #[test]
+    fn next_level_keeps_the_score() {
+        let mut board = board();
+        board.score = 1234;
+
+        clear_level(&mut board);
+        board.next_level();
+
+        assert_eq!(board.level.number, 2);
+        assert_eq!(board.score, 1234);
+    }
+
+    #[test]
+    fn next_level_after_game_over_starts_a_new_game() {
+        let mut board = board();
+        clear_level(&mut board);
+        board.next_level();
+        board.score = 1234;
+        board.player.state.hp = 0;
+        board.game_over = true;
+
+        board.next_level();
+
+        assert_eq!(board.level.number, 1);
+        assert_eq!(board.score, 0);
+        assert_eq!(board.player.state.hp, DEFAULT_LIVES);
+        assert!(!board.game_over);
+    }
+
+    #[test]
+    fn new_games_start_at_the_start_level() {
+        let assets = Assets::builtin();
+        let levels = level_file::builtin_levels(&assets);
+        let mut board = Board::starting_at(levels, assets, DEFAULT_LIVES, 42, 3);
+        assert_eq!(board.level.number, 3);
+
+        clear_level(&mut board);
+        board.next_level();
+        board.game_over = true;
+        board.next_level();
+
+        assert_eq!(board.level.number, 3);
+    }
+
+    #[test]
+    fn difficulty_scales_the_aliens() {
+        let hp = |board: &Board| {
+            board
+                .level
+                .aliens
+                .values()
+                .map(|alien| alien.state().hp)
+                .sum::<u16>()
+        };
+        let normal = board();
+
+        let nightmare = board().with_difficulty(Difficulty::Nightmare);
+
+        assert_eq!(nightmare.level.number, 1);
+        assert_eq!(hp(&nightmare), 2 * hp(&normal));
+    }
+
+    #[test]
+    fn difficulty_scales_a_marching_swarm() {
+        let assets = Assets::builtin();
+        let levels = level_file::builtin_levels(&assets);
+        let marching = levels.iter().position(|def| def.march.is_some()).unwrap() as u16 + 1;
+        let speeds = |difficulty| {
+            let board =
+                Board::starting_at(levels.clone(), assets.clone(), DEFAULT_LIVES, 42, marching)
+                    .with_difficulty(difficulty);
+            let march = board.level.march.as_ref().unwrap();
+            (march.speed, march.min_speed)
+        };
+
+        let (easy, normal, nightmare) = (
+            speeds(Difficulty::Easy),
+            speeds(Difficulty::Normal),
+            speeds(Difficulty::Nightmare),
+        );
+
+        assert!(easy.0 > normal.0 && normal.0 > nightmare.0);
+        assert!(easy.1 >= normal.1 && normal.1 >= nightmare.1);
+    }
+
+    #[test]
+    fn destroying_an_alien_scores_its_points() {
+        let mut board = scoring_board(1);
+        let ids = board.level.aliens.keys().copied().collect::<Vec<_>>();
+        let (fighter, tank, boss) = (ids[0], ids[1], ids[3]);
+
+        let points = [fighter, tank, boss].map(|id| destroy_alien(&mut board, id));
+
+        assert_eq!(points, [10, 20, 500 + BOSS_KILL_BONUS]);
+        assert_eq!(board.score, 1530);
+        assert_eq!(board.level.aliens.len(), 1);
+    }
+
+    #[test]
+    fn clearing_a_level_scores_a_bonus_once() {
+        let mut board = scoring_board(3);
+        let ids = board.level.aliens.keys().copied().collect::<Vec<_>>();
+
+        let points = ids
+            .iter()
+            .map(|id| destroy_alien(&mut board, *id))
+            .collect::<Vec<_>>();
+        for _ in 0..10 {
+            board.update();
+        }
+
+        let boss = 500 + BOSS_KILL_BONUS;
+        assert_eq!(points, vec![10, 20, 50, boss + 3 * LEVEL_CLEAR_BONUS]);
+        assert!(board.level.is_finished());
+        assert_eq!(board.score, 1880);
+    }
+
+    #[test]
+    fn alien_bullets_erode_bunkers() {
+        let mut board = bunker_board();
+        add_bullet(&mut board, 20, 10, SpriteCategory::AlienBullet);
+
+        board.update();
+
+        assert_eq!(bunker_cells(&board), vec![' ', '#']);
+        assert!(board.level.bullets.is_empty());
+    }
+
+    #[test]
+    fn player_bullets_erode_bunkers() {
+        let mut board = bunker_board();
+        add_bullet(&mut board, 21, 10, SpriteCategory::PlayerBullet);
+
+        board.update();
+
+        assert_eq!(bunker_cells(&board), vec!['#', ' ']);
+        assert!(board.level.player_bullets.is_empty());
+    }
+
+    #[test]
+    fn bullets_pass_through_eroded_cells() {
+        let mut board = bunker_board();
+        add_bullet(&mut board, 20, 10, SpriteCategory::AlienBullet);
+        board.update();
+
+        add_bullet(&mut board, 20, 10, SpriteCategory::PlayerBullet);
+        board.update();
+
+        assert_eq!(bunker_cells(&board), vec![' ', '#']);
+        assert_eq!(board.level.player_bullets.len(), 1);
+    }
+
+    #[test]
+    fn emptied_bunkers_are_removed() {
+        let mut board = bunker_board();
+        add_bullet(&mut board, 20, 10, SpriteCategory::AlienBullet);
+        add_bullet(&mut board, 21, 10, SpriteCategory::PlayerBullet);
+
+        board.update();
+
+        assert!(board.level.bunkers.is_empty());
+        assert!(board.level.bullets.is_empty());
+        assert!(board.level.player_bullets.is_empty());
+    }
+
+    #[test]
+    fn a_hit_costs_a_life_and_respawns_the_ship() {
+        let mut board = quiet_board();
+        board.move_player(Direction::Right);
+        for _ in 0..5 {
+            board.update();
+        }
+        assert_ne!(board.player.state.pos, Board::player_spawn_point());
+
+        shoot_player(&mut board);
+        board.update();
+
+        assert_eq!(board.player.state.hp, DEFAULT_LIVES - 1);
+        assert_eq!(board.player.state.pos, Board::player_spawn_point());
+        assert_eq!(board.player.ai.do_move, Direction::None);
+        assert!(board.player.is_invulnerable());
+        assert!(board.level.bullets.is_empty());
+        assert!(!board.game_over);
+    }
+
+    #[test]
+    fn bullets_pass_through_a_respawned_ship() {
+        let mut board = quiet_board();
+        shoot_player(&mut board);
+        board.update();
+        shoot_player(&mut board);
+
+        for _ in 1..RESPAWN_INVULNERABLE_TICKS {
+            board.update();
+            assert_eq!(board.player.state.hp, DEFAULT_LIVES - 1);
+            assert_eq!(board.level.bullets.len(), 1);
+        }
+        board.update();
+
+        assert_eq!(board.player.state.hp, DEFAULT_LIVES - 2);
+        assert!(board.level.bullets.is_empty());
+    }
+
+    #[test]
+    fn a_respawned_ship_blinks_until_it_can_be_hit() {
+        let mut board = quiet_board();
+        assert!(board.player.is_visible());
+        shoot_player(&mut board);
+        board.update();
+
+        let visible = (0..RESPAWN_INVULNERABLE_TICKS)
+            .map(|_| {
+                board.update();
+                board.player.is_visible()
+            })
+            .collect::<Vec<_>>();
+
+        assert!(visible.contains(&false));
+        assert!(visible.windows(2).any(|w| !w[0] && w[1]));
+        assert!(!board.player.is_invulnerable());
+        assert!(board.player.is_visible());
+    }
+
+    #[test]
+    fn losing_the_last_life_ends_the_game() {
+        let mut board = quiet_board();
+        board.player.state.hp = 1;
+
+        shoot_player(&mut board);
+        board.update();
+
+        assert_eq!(board.player.state.hp, 0);
+        assert!(board.game_over);
+    }
+
+    #[test]
+    fn new_games_start_with_the_board_lives() {
+        let assets = Assets::builtin();
+        let levels = level_file::builtin_levels(&assets);
+        let mut board = Board::new(levels, assets, 5, 42);
+        assert_eq!(board.player.state.hp, 5);
+        board.player.state.hp = 0;
+        board.game_over = true;
+
+        board.next_level();
+
+        assert_eq!(board.player.state.hp, 5);
+    }
+
+    #[test]
+    fn held_direction_moves_the_ship_again_after_a_respawn() {
+        let mut board = quiet_board();
+        board.move_player(Direction::Left);
+        board.update();
+        shoot_player(&mut board);
+        board.update();
+        assert_eq!(board.player.state.hp, DEFAULT_LIVES - 1);
+        let spawn = board.player.state.pos;
+
+        for _ in 0..3 {
+            board.hold_player(Direction::Left);
+            board.update();
+        }
+
+        assert_eq!(board.player.state.pos.x, spawn.x - 3);
+    }
+
+    #[test]
+    fn holding_leaves_a_mouse_target_alone() {
+        let mut board = quiet_board();
+        let x = board.player.state.fire_point().x;
+        board.move_player_to(x + 2);
+
+        board.hold_player(Direction::None);
+        board.update();
+        board.hold_player(Direction::None);
+        board.update();
+
+        assert_eq!(board.player.state.fire_point().x, x + 2);
+    }
+
+    #[test]
+    fn a_swarm_reaching_the_player_row_ends_the_game() {
+        let right = SCREEN_WIDTH as i16 - 1;
+        let mut above = marching_board(&[(right, 3)], None);
+        let mut landing = marching_board(&[(right, 2)], None);
+
+        above.update();
+        landing.update();
+
+        assert_eq!(above.level.aliens.values().next().unwrap().state().pos.y, 2);
+        assert!(!above.game_over);
+        assert!(landing.game_over);
+    }
+
+    #[test]
+    fn a_low_boss_does_not_land_the_swarm() {
+        let mut board = marching_board(&[(40, 20)], Some((5, 1)));
+
+        board.update();
+
+        assert!(!board.game_over);
+    }
+
+    #[test]
+    fn boss_level_spawns_the_boss() {
+        let mut board = board();
+        while !board.level.aliens.values().any(|alien| alien.is_boss()) {
+            assert!(board.level.number < board.levels.len() as u16);
+            clear_level(&mut board);
+            board.next_level();
+        }
+
+        assert_eq!(board.level.number, 4);
     }
 }
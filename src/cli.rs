@@ -0,0 +1,178 @@
+pub const USAGE: &str = "\
+Usage: rustivanders [OPTIONS]
+
+Options:
+  --level <n>       Start every game at level <n> instead of the first
+  --difficulty <d>  easy, normal, hard or nightmare, normal by default
+  --lives <n>       Lives per game, 3 by default
+  --seed <number>   Seed for all gameplay randomness, makes runs reproducible
+  --tick-rate <ms>  Length of a simulation step in milliseconds, 16 by default
+  --config <file>   Read key bindings from <file> instead of the default config
+  --record <file>   Record every input of the session to a replay file
+  --replay <file>   Play back a recorded session, 'q' or Esc stops it
+  --headless <n>    Simulate up to <n> ticks without a terminal and print the result
+  --script <file>   Input script for '--headless', the autopilot plays otherwise
+  --list-levels     Print the levels that would be played and exit
+  -h, --help        Print this help and exit
+  -V, --version     Print the version and exit";
+
+use rustivanders::difficulty::Difficulty;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Command line options.
+#[derive(Debug, Default)]
+pub struct Options {
+    pub level: Option<u16>,
+    pub difficulty: Option<Difficulty>,
+    pub lives: Option<u16>,
+    pub seed: Option<u64>,
+    pub tick_rate: Option<Duration>,
+    pub config: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub headless: Option<u64>,
+    pub script: Option<PathBuf>,
+    pub list_levels: bool,
+    pub help: bool,
+    pub version: bool,
+}
+
+impl Options {
+    /// Parses the arguments following the program name.
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--level" => {
+                    let level = parse_value(&arg, args.next())?;
+                    if level == 0 {
+                        return Err("levels are counted from 1".to_string());
+                    }
+                    options.level = Some(level);
+                }
+                "--difficulty" => options.difficulty = Some(parse_value(&arg, args.next())?),
+                "--lives" => {
+                    let lives = parse_value(&arg, args.next())?;
+                    if lives == 0 {
+                        return Err("a game needs at least one life".to_string());
+                    }
+                    options.lives = Some(lives);
+                }
+                "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+                "--tick-rate" => {
+                    let ms = parse_value(&arg, args.next())?;
+                    if ms == 0 {
+                        return Err("the tick rate must be at least 1 ms".to_string());
+                    }
+                    options.tick_rate = Some(Duration::from_millis(ms));
+                }
+                "--config" => options.config = Some(parse_value(&arg, args.next())?),
+                "--record" => options.record = Some(parse_value(&arg, args.next())?),
+                "--replay" => options.replay = Some(parse_value(&arg, args.next())?),
+                "--headless" => options.headless = Some(parse_value(&arg, args.next())?),
+                "--script" => options.script = Some(parse_value(&arg, args.next())?),
+                "--list-levels" => options.list_levels = true,
+                "-h" | "--help" => options.help = true,
+                "-V" | "--version" => options.version = true,
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        if options.headless.is_some() {
+            if options.record.is_some() || options.replay.is_some() {
+                return Err(
+                    "'--headless' cannot be combined with '--record' or '--replay'".to_string(),
+                );
+            }
+            if options.tick_rate.is_some() {
+                return Err(
+                    "'--headless' runs without a clock, '--tick-rate' is not allowed".to_string(),
+                );
+            }
+            if options.config.is_some() {
+                return Err("'--headless' reads no keys, '--config' is not allowed".to_string());
+            }
+        } else if options.script.is_some() {
+            return Err("'--script' requires '--headless'".to_string());
+        }
+        if options.replay.is_some() {
+            if options.record.is_some() {
+                return Err("'--replay' cannot be combined with '--record'".to_string());
+            }
+            for (flag, given) in [
+                ("--seed", options.seed.is_some()),
+                ("--level", options.level.is_some()),
+                ("--difficulty", options.difficulty.is_some()),
+                ("--lives", options.lives.is_some()),
+                ("--tick-rate", options.tick_rate.is_some()),
+                ("--config", options.config.is_some()),
+            ] {
+                if given {
+                    return Err(format!(
+                        "'--replay' uses the recorded session, '{}' is not allowed",
+                        flag
+                    ));
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for '{}'", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for '{}'", value, flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_the_session_options() {
+        let options = parse(&[
+            "--level",
+            "3",
+            "--difficulty",
+            "hard",
+            "--lives",
+            "5",
+            "--tick-rate",
+            "20",
+            "--config",
+            "keys",
+            "--record",
+            "out.replay",
+        ])
+        .unwrap();
+
+        assert_eq!(options.level, Some(3));
+        assert_eq!(options.difficulty, Some(Difficulty::Hard));
+        assert_eq!(options.lives, Some(5));
+        assert_eq!(options.tick_rate, Some(Duration::from_millis(20)));
+        assert_eq!(options.config, Some(PathBuf::from("keys")));
+        assert_eq!(options.record, Some(PathBuf::from("out.replay")));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["--level", "0"]).is_err());
+        assert!(parse(&["--level", "two"]).is_err());
+        assert!(parse(&["--tick-rate", "0"]).is_err());
+        assert!(parse(&["--lives", "0"]).is_err());
+        assert!(parse(&["--difficulty", "insane"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--replay", "a", "--level", "2"]).is_err());
+        assert!(parse(&["--replay", "a", "--lives", "5"]).is_err());
+        assert!(parse(&["--replay", "a", "--config", "keys"]).is_err());
+        assert!(parse(&["--headless", "100", "--tick-rate", "20"]).is_err());
+        assert!(parse(&["--headless", "100", "--config", "keys"]).is_err());
+        assert!(parse(&["--fast"]).is_err());
+    }
+}
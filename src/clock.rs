@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+/// Upper bound of simulation steps run for a single frame. When the game
+/// falls further behind than this the backlog is dropped instead of trying
+/// to catch up forever.
+const MAX_CATCH_UP_TICKS: u32 = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub tick_rate: Duration,
+    pub frame_rate: Duration,
+}
+
+/// Fixed timestep clock. Simulation ticks are paid out of an accumulator of
+/// elapsed real time so the game runs at the same speed no matter how fast
+/// the terminal renders, while redraws are throttled to the frame rate.
+pub struct Clock {
+    config: Config,
+    accumulator: Duration,
+    last_update: Instant,
+    last_render: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new(config: Config, now: Instant) -> Self {
+        Clock {
+            config,
+            accumulator: Duration::ZERO,
+            last_update: now,
+            last_render: None,
+        }
+    }
+
+    /// Number of ticks to simulate to catch up with `now`.
+    pub fn ticks_due(&mut self, now: Instant) -> u32 {
+        self.accumulator += now.saturating_duration_since(self.last_update);
+        self.last_update = now;
+
+        let mut ticks = 0;
+        while self.accumulator >= self.config.tick_rate {
+            self.accumulator -= self.config.tick_rate;
+            ticks += 1;
+            if ticks == MAX_CATCH_UP_TICKS {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+        }
+        ticks
+    }
+
+    /// Whether a frame may be drawn at `now`, marking it drawn if so.
+    pub fn render_due(&mut self, now: Instant) -> bool {
+        let due = match self.last_render {
+            Some(last) => now.saturating_duration_since(last) >= self.config.frame_rate,
+            None => true,
+        };
+        if due {
+            self.last_render = Some(now);
+        }
+        due
+    }
+
+    /// How long to wait for input before the next tick or frame is due.
+    pub fn timeout(&self, now: Instant) -> Duration {
+        let next_tick = self
+            .config
+            .tick_rate
+            .saturating_sub(self.accumulator + now.saturating_duration_since(self.last_update));
+        let next_frame = match self.last_render {
+            Some(last) => self
+                .config
+                .frame_rate
+                .saturating_sub(now.saturating_duration_since(last)),
+            None => Duration::ZERO,
+        };
+        next_tick.min(next_frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(16);
+    const FRAME: Duration = Duration::from_millis(33);
+
+    fn clock(start: Instant) -> Clock {
+        Clock::new(
+            Config {
+                tick_rate: TICK,
+                frame_rate: FRAME,
+            },
+            start,
+        )
+    }
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn ticks_due_pays_out_accumulated_time() {
+        let start = Instant::now();
+        let mut clock = clock(start);
+
+        assert_eq!(clock.ticks_due(start + ms(10)), 0);
+        // The 10 ms carried over make up a tick with 6 more
+        assert_eq!(clock.ticks_due(start + ms(16)), 1);
+        assert_eq!(clock.ticks_due(start + ms(50)), 2);
+        assert_eq!(clock.ticks_due(start + ms(64)), 1);
+        assert_eq!(clock.ticks_due(start + ms(64)), 0);
+    }
+
+    #[test]
+    fn ticks_due_drops_a_backlog_past_the_cap() {
+        let start = Instant::now();
+        let mut clock = clock(start);
+
+        assert_eq!(clock.ticks_due(start + ms(5000)), MAX_CATCH_UP_TICKS);
+        // The rest of the backlog is gone, not paid out later
+        assert_eq!(clock.ticks_due(start + ms(5000)), 0);
+        assert_eq!(clock.ticks_due(start + ms(5016)), 1);
+    }
+
+    #[test]
+    fn render_due_throttles_to_the_frame_rate() {
+        let start = Instant::now();
+        let mut clock = clock(start);
+
+        assert!(clock.render_due(start));
+        assert!(!clock.render_due(start + ms(1)));
+        assert!(!clock.render_due(start + ms(32)));
+        assert!(clock.render_due(start + ms(33)));
+        assert!(!clock.render_due(start + ms(60)));
+        assert!(clock.render_due(start + ms(66)));
+    }
+
+    #[test]
+    fn timeout_waits_for_the_next_tick_or_frame() {
+        let start = Instant::now();
+        let mut clock = clock(start);
+
+        // Nothing drawn yet, a frame is due right away
+        assert_eq!(clock.timeout(start), Duration::ZERO);
+
+        clock.render_due(start);
+        assert_eq!(clock.timeout(start), TICK);
+        assert_eq!(clock.timeout(start + ms(10)), ms(6));
+
+        clock.ticks_due(start + ms(20));
+        assert_eq!(clock.timeout(start + ms(20)), ms(12));
+        assert_eq!(clock.timeout(start + ms(30)), ms(2));
+
+        clock.ticks_due(start + ms(32));
+        // The frame at 33 ms comes before the tick at 48 ms
+        assert_eq!(clock.timeout(start + ms(32)), ms(1));
+        assert_eq!(clock.timeout(start + ms(40)), Duration::ZERO);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::bindings::{self, Action, Bindings, PRESETS};
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use termion::event::Key;
+
+const APP_DIR: &str = "rustivanders";
+const FILE_NAME: &str = "config";
+
+/// Settings read from the user's config file.
+///
+/// ```text
+/// [keys]
+/// preset = wasd        # arrows, wasd or vim
+/// fire = space, up     # replaces the preset's keys for an action
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserConfig {
+    pub bindings: Bindings,
+}
+
+impl UserConfig {
+    /// Reads the config at `path`. A missing file is the default config.
+    pub fn load(path: &Path) -> Result<UserConfig, ConfigError> {
+        let file = path.display().to_string();
+        match fs::read_to_string(path) {
+            Ok(src) => UserConfig::parse(&file, &src),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(UserConfig::default()),
+            Err(e) => Err(ConfigError::new(&file, None, e.to_string())),
+        }
+    }
+
+    /// Like `load`, but a missing file is an error, for configs named on
+    /// the command line.
+    pub fn read(path: &Path) -> Result<UserConfig, ConfigError> {
+        if !path.exists() {
+            let file = path.display().to_string();
+            return Err(ConfigError::new(&file, None, "no such file".to_string()));
+        }
+        UserConfig::load(path)
+    }
+
+    pub fn parse(file: &str, src: &str) -> Result<UserConfig, ConfigError> {
+        let mut config = UserConfig::default();
+        let mut section = None;
+        let mut bound: Vec<(Key, Action)> = Vec::new();
+
+        for (idx, raw) in src.lines().enumerate() {
+            let error = |message: String| ConfigError::new(file, Some(idx + 1), message);
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                match name.trim() {
+                    "keys" => section = Some("keys"),
+                    other => return Err(error(format!("unknown section [{}]", other))),
+                }
+                continue;
+            }
+            if section.is_none() {
+                return Err(error("settings must follow a [section]".to_string()));
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| error(format!("expected 'key = value', got '{}'", line)))?;
+
+            if key == "preset" {
+                if !bound.is_empty() {
+                    return Err(error("'preset' must come before any key".to_string()));
+                }
+                config.bindings = Bindings::preset(value).ok_or_else(|| {
+                    error(format!(
+                        "unknown preset '{}', expected one of {}",
+                        value,
+                        PRESETS.join(", ")
+                    ))
+                })?;
+                continue;
+            }
+
+            let action =
+                Action::from_name(key).ok_or_else(|| error(format!("unknown action '{}'", key)))?;
+            config.bindings.unbind(action);
+            for name in value.split(',').map(str::trim) {
+                let key = bindings::parse_key(name)
+                    .ok_or_else(|| error(format!("unknown key '{}'", name)))?;
+                if let Some((_, other)) = bound.iter().find(|(k, _)| *k == key) {
+                    return Err(error(format!(
+                        "'{}' is bound to both {} and {}",
+                        name,
+                        other.name(),
+                        action.name()
+                    )));
+                }
+                bound.push((key, action));
+                config.bindings.bind(action, key);
+            }
+        }
+
+        for action in Action::ALL {
+            if config.bindings.keys(action).is_empty() {
+                return Err(ConfigError::new(
+                    file,
+                    None,
+                    format!("no key left for '{}'", action.name()),
+                ));
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rustivanders/config`, falling back to `~/.config` as
+/// the XDG base directory spec requires.
+pub fn default_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join(APP_DIR).join(FILE_NAME))
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(file: &str, line: Option<usize>, message: String) -> Self {
+        ConfigError {
+            file: file.to_string(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_override_the_preset() {
+        let src = "[keys]\npreset = vim\nfire = up, f # both\n";
+
+        let config = UserConfig::parse("config", src).unwrap();
+
+        let bindings = config.bindings;
+        assert_eq!(bindings.action(Key::Char('h')), Some(Action::MoveLeft));
+        assert_eq!(bindings.keys(Action::Fire), vec![Key::Up, Key::Char('f')]);
+        assert_eq!(bindings.action(Key::Char(' ')), None);
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        let cases = [
+            ("preset = vim", "config:1: settings must follow a [section]"),
+            ("[keys]\njump = j", "config:2: unknown action 'jump'"),
+            ("[keys]\nfire = meta-x", "config:2: unknown key 'meta-x'"),
+            (
+                "[keys]\nfire = x\npause = x",
+                "config:3: 'x' is bound to both fire and pause",
+            ),
+            (
+                "[keys]\nquit = q\npreset = wasd",
+                "config:3: 'preset' must come before any key",
+            ),
+            ("[keys]\nfire = p", "config: no key left for 'pause'"),
+        ];
+        for (src, expected) in cases {
+            let error = UserConfig::parse("config", src).unwrap_err();
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Panic handling for the terminal game.
+//!
+//! A panic message printed while the game owns the terminal lands on the
+//! alternate screen and is gone once the screen is restored. The hook
+//! installed here keeps the message instead, so it can be shown after the
+//! terminal guards have been dropped and written to a crash report along
+//! with the state of the game.
+
+use std::backtrace::Backtrace;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::panic;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const APP_DIR: &str = "rustivanders";
+
+/// The first panic of the process with its backtrace.
+static PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Replaces the default hook, which would print to the alternate screen.
+pub fn install_hook() {
+    panic::set_hook(Box::new(|info| {
+        let report = format!("{}\n\nBacktrace:\n{}", info, Backtrace::force_capture());
+        let mut panic = PANIC.lock().unwrap_or_else(|e| e.into_inner());
+        panic.get_or_insert(report);
+    }));
+}
+
+/// Whether any thread panicked since the hook was installed.
+pub fn panicked() -> bool {
+    PANIC.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// Writes a report of the recorded panic and `state`, the game as it was
+/// when the panic hit, and tells the user about it on stderr. Must only be
+/// called once the terminal is restored.
+pub fn report(state: Option<String>) {
+    // termion leaves the alternate screen with a write that sits in stdout's
+    // buffer, it has to go out before the message
+    let _ = io::stdout().flush();
+    let panic = PANIC
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_else(|| "panicked without a message".to_string());
+    let message = panic.split("\n\nBacktrace:").next().unwrap_or_default();
+    eprintln!("rustivanders crashed, sorry about that.\n\n{}\n", message);
+
+    let report = match &state {
+        Some(state) => format!("{}\n\nGame state:\n{}", panic, state),
+        None => panic,
+    };
+    match write_report(&report) {
+        Ok(path) => eprintln!("A crash report was written to {}", path.display()),
+        Err(e) => eprintln!("Could not write a crash report: {}\n\n{}", e, report),
+    }
+}
+
+fn write_report(report: &str) -> io::Result<PathBuf> {
+    let dir = report_dir();
+    fs::create_dir_all(&dir)?;
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("crash-{}.txt", secs));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// `$XDG_STATE_HOME/rustivanders`, falling back to `~/.local/state` as the
+/// XDG base directory spec requires, or the temp directory without a home.
+fn report_dir() -> PathBuf {
+    env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+        .unwrap_or_else(env::temp_dir)
+        .join(APP_DIR)
+}
@@ -0,0 +1,185 @@
+use crate::level_file::{AiParams, MarchDef};
+use std::str::FromStr;
+
+/// Shots the boss fires per burst on `Normal`.
+const BOSS_BURST: u16 = 10;
+
+/// How hard the aliens play. Level files describe `Normal`, the other
+/// presets scale what they say.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+/// Factors applied to the level file values. Speeds and fire rates are
+/// delays in ticks, so a factor below 1 makes the aliens faster.
+struct Scaling {
+    move_delay: f64,
+    fire_delay: f64,
+    bullet_delay: f64,
+    hp: f64,
+    burst: f64,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Nightmare,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Nightmare => "nightmare",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    /// The next harder preset, wrapping around to `Easy`.
+    pub fn next(&self) -> Difficulty {
+        let idx = Difficulty::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Difficulty::ALL[(idx + 1) % Difficulty::ALL.len()]
+    }
+
+    fn scaling(&self) -> Scaling {
+        match self {
+            Difficulty::Easy => Scaling {
+                move_delay: 1.5,
+                fire_delay: 1.6,
+                bullet_delay: 1.5,
+                hp: 0.5,
+                burst: 0.6,
+            },
+            Difficulty::Normal => Scaling {
+                move_delay: 1.0,
+                fire_delay: 1.0,
+                bullet_delay: 1.0,
+                hp: 1.0,
+                burst: 1.0,
+            },
+            Difficulty::Hard => Scaling {
+                move_delay: 0.75,
+                fire_delay: 0.7,
+                bullet_delay: 0.75,
+                hp: 1.5,
+                burst: 1.5,
+            },
+            Difficulty::Nightmare => Scaling {
+                move_delay: 0.5,
+                fire_delay: 0.45,
+                bullet_delay: 0.5,
+                hp: 2.0,
+                burst: 2.0,
+            },
+        }
+    }
+
+    /// Movement, firing and bullet speed of an alien.
+    pub fn ai(&self, params: AiParams) -> AiParams {
+        let scaling = self.scaling();
+        AiParams {
+            move_speed: scale(params.move_speed, scaling.move_delay),
+            fire_rate: params.fire_rate * scaling.fire_delay,
+            bullet_speed: ((params.bullet_speed as f64 * scaling.bullet_delay).round() as i32)
+                .max(1),
+        }
+    }
+
+    /// Step delays of a marching swarm, which moves its aliens in place of
+    /// their own AI.
+    pub fn march(&self, march: MarchDef) -> MarchDef {
+        let move_delay = self.scaling().move_delay;
+        MarchDef {
+            speed: scale(march.speed, move_delay),
+            min_speed: scale(march.min_speed, move_delay),
+            step_down: march.step_down,
+        }
+    }
+
+    /// Hits an alien takes, never less than one.
+    pub fn hp(&self, hp: u16) -> u16 {
+        scale(hp, self.scaling().hp)
+    }
+
+    /// Shots the boss fires per burst.
+    pub fn boss_burst(&self) -> u16 {
+        scale(BOSS_BURST, self.scaling().burst)
+    }
+}
+
+fn scale(value: u16, factor: f64) -> u16 {
+    ((value as f64 * factor).round() as u16).max(1)
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::from_name(s).ok_or_else(|| format!("unknown difficulty '{}'", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_keeps_the_level_file_values() {
+        let params = AiParams::new(10, 10.0, 8);
+
+        assert_eq!(Difficulty::Normal.ai(params), params);
+        assert_eq!(Difficulty::Normal.hp(20), 20);
+        assert_eq!(Difficulty::Normal.boss_burst(), 10);
+    }
+
+    #[test]
+    fn harder_presets_are_faster_and_tougher() {
+        let params = AiParams::new(10, 10.0, 8);
+        let mut last = Difficulty::Easy;
+        for difficulty in &Difficulty::ALL[1..] {
+            let (easier, harder) = (last.ai(params), difficulty.ai(params));
+            assert!(harder.move_speed < easier.move_speed);
+            assert!(harder.fire_rate < easier.fire_rate);
+            assert!(harder.bullet_speed < easier.bullet_speed);
+            let march = MarchDef {
+                speed: 30,
+                min_speed: 4,
+                step_down: 1,
+            };
+            assert!(difficulty.march(march).speed < last.march(march).speed);
+            assert!(difficulty.hp(20) > last.hp(20));
+            assert!(difficulty.boss_burst() > last.boss_burst());
+            last = *difficulty;
+        }
+    }
+
+    #[test]
+    fn scaled_values_stay_usable() {
+        let params = AiParams::new(1, 0.0, 1);
+
+        let scaled = Difficulty::Nightmare.ai(params);
+
+        assert_eq!(scaled, AiParams::new(1, 0.0, 1));
+        assert_eq!(Difficulty::Easy.hp(1), 1);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::config::ConfigError;
+use crate::replay::ReplayError;
+
+use rustivanders::headless::ScriptError;
+use rustivanders::level_file::LevelFileError;
+use rustivanders::sprite::AssetError;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can stop the game short of a bug.
+#[derive(Debug)]
+pub enum Error {
+    /// The terminal could not be set up or drawn to.
+    Terminal(io::Error),
+    /// Reading keyboard or mouse input failed.
+    Input(io::Error),
+    /// Standard input was closed.
+    InputClosed,
+    Assets(AssetError),
+    Levels(LevelFileError),
+    Config(ConfigError),
+    Replay(ReplayError),
+    Script(ScriptError),
+    /// A recording could not be written.
+    Record(PathBuf, io::Error),
+    /// The start level is past the last level loaded.
+    NoSuchLevel {
+        level: u16,
+        count: usize,
+    },
+}
+
+impl Error {
+    /// Process exit code: 1 for files that can't be read or written, 2 for
+    /// bad arguments and 3 for terminal and input failures. Crashes exit
+    /// with 101.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NoSuchLevel { .. } => 2,
+            Error::Terminal(_) | Error::Input(_) | Error::InputClosed => 3,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Terminal(e) => write!(f, "Terminal error: {}", e),
+            Error::Input(e) => write!(f, "Failed to read input: {}", e),
+            Error::InputClosed => write!(f, "Input was closed"),
+            Error::Assets(e) => write!(f, "Failed to load sprite assets: {}", e),
+            Error::Levels(e) => write!(f, "Failed to load levels: {}", e),
+            Error::Config(e) => write!(f, "Failed to load config: {}", e),
+            Error::Replay(e) => write!(f, "Failed to load replay: {}", e),
+            Error::Script(e) => write!(f, "Failed to load script: {}", e),
+            Error::Record(path, e) => write!(f, "Failed to record to {}: {}", path.display(), e),
+            Error::NoSuchLevel { level, count } => {
+                write!(
+                    f,
+                    "There is no level {}, only {} levels were loaded",
+                    level, count
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Terminal(e) | Error::Input(e) | Error::Record(_, e) => Some(e),
+            Error::InputClosed | Error::NoSuchLevel { .. } => None,
+            Error::Assets(e) => Some(e),
+            Error::Levels(e) => Some(e),
+            Error::Config(e) => Some(e),
+            Error::Replay(e) => Some(e),
+            Error::Script(e) => Some(e),
+        }
+    }
+}
+
+impl From<AssetError> for Error {
+    fn from(e: AssetError) -> Self {
+        Error::Assets(e)
+    }
+}
+
+impl From<LevelFileError> for Error {
+    fn from(e: LevelFileError) -> Self {
+        Error::Levels(e)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Error::Config(e)
+    }
+}
+
+impl From<ReplayError> for Error {
+    fn from(e: ReplayError) -> Self {
+        Error::Replay(e)
+    }
+}
+
+impl From<ScriptError> for Error {
+    fn from(e: ScriptError) -> Self {
+        Error::Script(e)
+    }
+}
@@ -1,70 +1,253 @@
 use std::io;
-use std::sync::mpsc;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError};
 use std::thread;
 use std::time::Duration;
 
-use std::io::{stdin, stdout, Write};
+use crate::bindings::Action;
+use crate::error::Error;
+
 use termion::event::Key;
 use termion::event::MouseEvent;
-use termion::input::MouseTerminal;
-use termion::input::TermRead;
-use termion::raw::IntoRawMode;
 
+#[cfg(not(feature = "crossterm"))]
+use crate::kitty;
+#[cfg(not(feature = "crossterm"))]
+use std::io::Write;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event<I, MI> {
     Input(I),
     MouseInput(MI),
-    Tick,
+    /// A key was let go, only reported by terminals speaking the kitty
+    /// keyboard protocol.
+    Release(I),
+    /// An action from a device without keys, such as a gamepad.
+    Pad {
+        action: Action,
+        pressed: bool,
+    },
+    /// The terminal was resized to the given columns and rows.
+    Resize(u16, u16),
 }
 
-/// A small event handler that wrap termion input and tick events. Each event
-/// type is handled in its own thread and returned to a common `Receiver`
-pub struct Events {
-    rx: mpsc::Receiver<Event<Key, MouseEvent>>,
-    input_handle: thread::JoinHandle<()>,
-    tick_handle: thread::JoinHandle<()>,
-}
+/// What the input threads send: an event, or the error that ended the
+/// thread. A closed input is reported as `UnexpectedEof`.
+pub type Message = io::Result<Event<Key, MouseEvent>>;
 
-#[derive(Debug, Clone, Copy)]
-pub struct Config {
-    pub tick_rate: Duration,
+/// A small event handler that wraps terminal input. Input is read in its own
+/// threads and returned to a `Receiver`, timing is left to the game loop.
+/// The terminal size is reported at start and whenever it changes.
+///
+/// Input is read with termion, or with crossterm when built with the
+/// `crossterm` feature. Both deliver termion's key and mouse types so the
+/// rest of the game doesn't care which one is used. Key releases are asked
+/// for with the kitty keyboard protocol in the termion build. Gamepads are
+/// read as well when built with the `gamepad` feature.
+pub struct Events {
+    rx: mpsc::Receiver<Message>,
+    _handles: Vec<thread::JoinHandle<()>>,
 }
 
 impl Events {
-    pub fn with_config(config: Config) -> Events {
+    pub fn new() -> Events {
         let (tx, rx) = mpsc::channel();
+        let mut handles = Vec::new();
+        #[cfg(feature = "gamepad")]
+        handles.extend(crate::gamepad::spawn(tx.clone()));
+        handles.extend(input::spawn(tx));
+        Events {
+            rx,
+            _handles: handles,
+        }
+    }
+
+    /// Waits up to `timeout` for the next input event, `None` when there
+    /// was none.
+    pub fn next_timeout(&self, timeout: Duration) -> Result<Option<Event<Key, MouseEvent>>, Error> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(message) => message.map(Some).map_err(input_error),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(Error::InputClosed),
+        }
+    }
+
+    /// Returns an already queued input event without waiting.
+    pub fn try_next(&self) -> Result<Option<Event<Key, MouseEvent>>, Error> {
+        match self.rx.try_recv() {
+            Ok(message) => message.map(Some).map_err(input_error),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(Error::InputClosed),
+        }
+    }
+}
+
+fn input_error(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::InputClosed,
+        _ => Error::Input(e),
+    }
+}
+
+/// Hands the terminal's keyboard mode back in the state it was found.
+#[cfg(not(feature = "crossterm"))]
+impl Drop for Events {
+    fn drop(&mut self) {
+        let mut stdout = std::io::stdout();
+        let _ = write!(stdout, "{}", kitty::DISABLE);
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(not(feature = "crossterm"))]
+mod input {
+    use super::{Event, Message};
+    use crate::kitty::{self, KeyEventKind};
+
+    use std::io::{self, stdout, Write};
+    use std::sync::mpsc::Sender;
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    use termion::input::TermRead;
+
+    /// How often the terminal size is checked, termion has no resize events.
+    const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    pub fn spawn(tx: Sender<Message>) -> Vec<JoinHandle<()>> {
         let input_handle = {
             let tx = tx.clone();
             thread::spawn(move || {
-                let stdin = io::stdin();
-                let mut stdout = MouseTerminal::from(stdout().into_raw_mode().unwrap());
-                for c in stdin.events() {
-                    let evt = c.unwrap();
-                    let _ = match evt {
-                        termion::event::Event::Key(key) => tx.send(Event::Input(key)),
-                        termion::event::Event::Mouse(me) => tx.send(Event::MouseInput(me)),
-                        _ => Ok(()),
-                    };
-                    stdout.flush().unwrap();
-                }
+                let error = match read_input(&tx) {
+                    Ok(()) => io::Error::new(io::ErrorKind::UnexpectedEof, "input closed"),
+                    Err(e) => e,
+                };
+                let _ = tx.send(Err(error));
             })
         };
-        let tick_handle = {
-            thread::spawn(move || loop {
-                if let Err(err) = tx.send(Event::Tick) {
-                    eprintln!("{}", err);
-                    break;
+        let resize_handle = thread::spawn(move || {
+            // Starts out unknown so the initial size is reported too
+            let mut last_size = None;
+            loop {
+                let size = termion::terminal_size().ok();
+                if size != last_size {
+                    last_size = size;
+                    if let Some((width, height)) = size {
+                        if tx.send(Ok(Event::Resize(width, height))).is_err() {
+                            return;
+                        }
+                    }
                 }
-                thread::sleep(config.tick_rate);
-            })
-        };
-        Events {
-            rx,
-            input_handle,
-            tick_handle,
+                thread::sleep(RESIZE_POLL_INTERVAL);
+            }
+        });
+        vec![input_handle, resize_handle]
+    }
+
+    /// Sends input events until stdin is closed or the game stops listening.
+    fn read_input(tx: &Sender<Message>) -> io::Result<()> {
+        // Raw mode and mouse reporting belong to the renderer's terminal.
+        // Terminals without the kitty protocol ignore this.
+        let mut stdout = stdout();
+        write!(stdout, "{}", kitty::ENABLE)?;
+        stdout.flush()?;
+        for event in io::stdin().events() {
+            let event = match event? {
+                termion::event::Event::Key(key) => Event::Input(key),
+                termion::event::Event::Mouse(me) => Event::MouseInput(me),
+                termion::event::Event::Unsupported(bytes) => match kitty::parse(&bytes) {
+                    Some((key, KeyEventKind::Release)) => Event::Release(key),
+                    Some((key, _)) => Event::Input(key),
+                    None => continue,
+                },
+            };
+            if tx.send(Ok(event)).is_err() {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "crossterm")]
+mod input {
+    use super::{Event, Message};
+
+    use std::sync::mpsc::Sender;
+    use std::thread::{self, JoinHandle};
+
+    use crossterm::event::{self as ct, KeyCode, KeyModifiers, MouseEventKind};
+    use termion::event::{Key, MouseButton, MouseEvent};
+
+    /// Raw mode and mouse capture are enabled by the renderer's terminal.
+    pub fn spawn(tx: Sender<Message>) -> Vec<JoinHandle<()>> {
+        let handle = thread::spawn(move || {
+            if let Ok((width, height)) = crossterm::terminal::size() {
+                let _ = tx.send(Ok(Event::Resize(width, height)));
+            }
+            loop {
+                let event = match ct::read() {
+                    Ok(ct::Event::Key(key)) => Some(Event::Input(key_event(key))),
+                    Ok(ct::Event::Mouse(me)) => mouse_event(me).map(Event::MouseInput),
+                    Ok(ct::Event::Resize(width, height)) => Some(Event::Resize(width, height)),
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                        return;
+                    }
+                };
+                if let Some(event) = event {
+                    if tx.send(Ok(event)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        vec![handle]
+    }
+
+    fn key_event(key: ct::KeyEvent) -> Key {
+        match key.code {
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Enter => Key::Char('\n'),
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Tab => Key::Char('\t'),
+            KeyCode::BackTab => Key::BackTab,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Insert => Key::Insert,
+            KeyCode::F(n) => Key::F(n),
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => Key::Ctrl(c),
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) => Key::Alt(c),
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Null => Key::Null,
+            KeyCode::Esc => Key::Esc,
         }
     }
 
-    pub fn next(&self) -> Result<Event<Key, MouseEvent>, mpsc::RecvError> {
-        self.rx.recv()
+    /// Crossterm counts cells from zero, termion from one.
+    fn mouse_event(me: ct::MouseEvent) -> Option<MouseEvent> {
+        let (x, y) = (me.column + 1, me.row + 1);
+        let event = match me.kind {
+            MouseEventKind::Down(button) => MouseEvent::Press(mouse_button(button), x, y),
+            MouseEventKind::Up(_) => MouseEvent::Release(x, y),
+            MouseEventKind::Drag(_) => MouseEvent::Hold(x, y),
+            MouseEventKind::ScrollUp => MouseEvent::Press(MouseButton::WheelUp, x, y),
+            MouseEventKind::ScrollDown => MouseEvent::Press(MouseButton::WheelDown, x, y),
+            MouseEventKind::Moved => return None,
+        };
+        Some(event)
+    }
+
+    fn mouse_button(button: ct::MouseButton) -> MouseButton {
+        match button {
+            ct::MouseButton::Left => MouseButton::Left,
+            ct::MouseButton::Right => MouseButton::Right,
+            ct::MouseButton::Middle => MouseButton::Middle,
+        }
     }
 }
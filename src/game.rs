@@ -1,89 +1,689 @@
-use crate::board::Board;
-use crate::events::{Config, Event, Events};
-use crate::renderer::Renderer;
-use crate::util::Direction;
-use std::time::Duration;
+use crate::bindings::{self, Action, Bindings};
+use crate::clock::{Clock, Config};
+use crate::crash;
+use crate::error::Error;
+use crate::events::{Event, Events};
+use crate::held_keys::HeldKeys;
+use crate::highscores::{self, HighScore, HighScores, INITIALS_LEN};
+use crate::replay::{InputEvent, Recorder, Replay};
+use crate::state::GameState;
+
+use rustivanders::board::{Board, DEFAULT_LIVES};
+use rustivanders::difficulty::Difficulty;
+use rustivanders::level_file::LevelDef;
+use rustivanders::renderer::{self, Overlay, Renderer};
+use rustivanders::sprite::Assets;
+use rustivanders::surface::FrameBuffer;
+use rustivanders::util::Direction;
+use std::io;
+use std::panic;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
-use std::io::stdout;
 use termion::event::{Key, MouseButton, MouseEvent};
-use termion::input::MouseTerminal;
-use termion::raw::IntoRawMode;
+use tui::style::Color;
+
+const TARGET_FPS: u64 = 30;
+
+/// Length of a simulation step.
+pub const TICK_RATE: Duration = Duration::from_millis(16);
 
 pub struct Rustivanders {
     is_exiting: bool,
+    state: GameState,
     board: Board,
     renderer: Renderer,
+    highscores: HighScores,
+    highscores_path: Option<PathBuf>,
+    highscores_error: Option<String>,
+    /// Seed requested on the command line, a random one is drawn per game otherwise.
+    seed: Option<u64>,
+    /// Simulation steps run so far, inputs are recorded and replayed against it.
+    tick: u64,
+    /// Length of a simulation step.
+    tick_rate: Duration,
+    recorder: Option<Recorder>,
+    /// Why recording stopped early, the game goes on without it.
+    recording_error: Option<Error>,
+    /// While set, inputs come from the replay and live input can only quit.
+    replay: Option<Replay>,
+    /// Set while the terminal is too small to show the game.
+    too_small: bool,
+    /// Columns and rows of the terminal, once known.
+    screen: Option<(u16, u16)>,
+    held_keys: HeldKeys,
+    /// Direction held on a gamepad, which reports releases itself and
+    /// takes precedence over the keyboard.
+    pad_direction: Direction,
+    /// Direction last taken from the held keys or the gamepad. A change
+    /// steers the player, overriding a mouse move. While it is unchanged
+    /// the ship keeps going, also after a respawn dropped its move.
+    held_direction: Direction,
+    bindings: Bindings,
+    /// Where the bindings were read from, shown on the bindings screen.
+    config_path: Option<PathBuf>,
 }
 
 impl Rustivanders {
-    pub fn new() -> Self {
-        Rustivanders {
+    pub fn new(levels: Vec<LevelDef>, assets: Assets, seed: Option<u64>) -> Result<Self, Error> {
+        let highscores_path = highscores::default_path();
+        let (highscores, highscores_error) = match &highscores_path {
+            Some(path) => match HighScores::load(path) {
+                Ok(table) => (table, None),
+                Err(e) => (
+                    HighScores::default(),
+                    Some(format!("Could not read {}: {}", path.display(), e)),
+                ),
+            },
+            None => (
+                HighScores::default(),
+                Some("No data directory, high scores will not be saved".to_string()),
+            ),
+        };
+
+        Ok(Rustivanders {
             is_exiting: false,
-            board: Board::new(),
-            renderer: Renderer::new(),
+            state: GameState::Title,
+            board: Board::new(
+                levels,
+                assets,
+                DEFAULT_LIVES,
+                seed.unwrap_or_else(rand::random),
+            ),
+            renderer: Renderer::new().map_err(Error::Terminal)?,
+            highscores,
+            highscores_path,
+            highscores_error,
+            seed,
+            tick: 0,
+            tick_rate: TICK_RATE,
+            recorder: None,
+            recording_error: None,
+            replay: None,
+            too_small: false,
+            screen: None,
+            held_keys: HeldKeys::default(),
+            pad_direction: Direction::None,
+            held_direction: Direction::None,
+            bindings: Bindings::default(),
+            config_path: None,
+        })
+    }
+
+    /// Starts every game at level `number` instead of the first.
+    pub fn with_start_level(mut self, number: u16) -> Self {
+        self.board.start_level = number;
+        self
+    }
+
+    /// Gives the player `lives` lives per game instead of `DEFAULT_LIVES`.
+    pub fn with_lives(mut self, lives: u16) -> Self {
+        self.board.lives = lives;
+        self.board.player.state.hp = lives;
+        self
+    }
+
+    /// Starts at `difficulty`, the title screen can change it.
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.board.difficulty = difficulty;
+        self
+    }
+
+    /// Runs a simulation step every `tick_rate` instead of every `TICK_RATE`.
+    pub fn with_tick_rate(mut self, tick_rate: Duration) -> Self {
+        self.tick_rate = tick_rate;
+        self.held_keys = HeldKeys::new(tick_rate);
+        self
+    }
+
+    /// Uses `bindings`, read from the config at `path`, instead of the
+    /// default keys.
+    pub fn with_bindings(mut self, bindings: Bindings, path: Option<PathBuf>) -> Self {
+        self.bindings = bindings;
+        self.config_path = path;
+        self
+    }
+
+    /// Records every handled input to `recorder`. The recorder must have
+    /// been created with the seed passed to `new`.
+    pub fn with_recorder(mut self, mut recorder: Recorder) -> Self {
+        let scores = self
+            .highscores
+            .entries
+            .iter()
+            .map(|entry| entry.score)
+            .collect::<Vec<_>>();
+        match recorder.record_high_scores(&scores) {
+            Ok(()) => self.recorder = Some(recorder),
+            Err(e) => self.recording_error = Some(Error::Record(recorder.path().to_path_buf(), e)),
         }
+        self
+    }
+
+    /// Plays back `replay` instead of reading the keyboard, with the start
+    /// level, lives, difficulty, tick rate and key bindings it was recorded with.
+    /// The local high score table is neither shown nor saved. The scores
+    /// recorded in its place decide whether a game over asks for initials,
+    /// as they did in the recorded session.
+    pub fn with_replay(mut self, mut replay: Replay) -> Self {
+        self = self
+            .with_start_level(replay.start_level)
+            .with_lives(replay.lives)
+            .with_difficulty(replay.difficulty)
+            .with_tick_rate(replay.tick_rate);
+        self.seed = Some(replay.seed);
+        self.bindings = replay.bindings.take().unwrap_or_default();
+        self.config_path = None;
+        self.highscores = replay
+            .high_scores
+            .as_deref()
+            .map(HighScores::from_scores)
+            .unwrap_or_default();
+        self.highscores_path = None;
+        self.highscores_error = None;
+        self.replay = Some(replay);
+        self
+    }
+
+    fn new_game(&mut self) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        self.board.new_game(seed);
+        self.state = GameState::Playing;
+        self.stop_player();
+    }
+
+    fn stop_player(&mut self) {
+        self.held_keys.clear();
+        self.held_direction = Direction::None;
+        self.board.move_player(Direction::None);
     }
 
-    pub fn run(&mut self) {
-        self.renderer.clear();
+    /// Plays until the player quits. A recording that failed part way is
+    /// reported once the game is over.
+    pub fn run(&mut self) -> Result<(), Error> {
+        self.renderer.clear().map_err(Error::Terminal)?;
 
-        let events = Events::with_config(Config {
-            tick_rate: Duration::from_millis(16),
-        });
+        let events = Events::new();
+        let mut clock = Clock::new(
+            Config {
+                tick_rate: self.tick_rate,
+                frame_rate: Duration::from_millis(1000 / TARGET_FPS),
+            },
+            Instant::now(),
+        );
+        let mut dirty = true;
 
         loop {
-            if let Ok(event) = events.next() {
+            let mut next = events.next_timeout(clock.timeout(Instant::now()))?;
+            while let Some(event) = next {
                 match event {
-                    Event::Input(input) => self.process_input(input),
-                    Event::MouseInput(me) => self.process_mouse_input(me),
-                    Event::Tick => self.update(),
+                    Event::Resize(width, height) => self.resize(width, height),
+                    event if self.replay.is_some() => self.replay_live_input(event),
+                    Event::MouseInput(me) => {
+                        if let Some(me) = self.playfield_mouse(me) {
+                            self.handle_event(Event::MouseInput(me));
+                        }
+                    }
+                    event => self.handle_event(event),
                 }
+                dirty = true;
+                next = events.try_next()?;
             }
 
-            self.render();
+            let now = Instant::now();
+            for _ in 0..clock.ticks_due(now) {
+                self.play_replay();
+                self.pause_if_hidden();
+                if self.is_exiting {
+                    break;
+                }
+                self.update();
+                dirty = true;
+            }
+
+            if dirty && clock.render_due(now) {
+                self.render().map_err(Error::Terminal)?;
+                dirty = false;
+            }
 
             if self.is_exiting {
                 break;
             }
+
+            // A panic in an input thread only ends that thread
+            if crash::panicked() {
+                panic::resume_unwind(Box::new("input thread panicked"));
+            }
+        }
+
+        match self.recording_error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// What the game was doing, for crash reports.
+    pub fn crash_state(&self) -> String {
+        let board = &self.board;
+        let player = &board.player.state;
+        format!(
+            "{}\ntick: {}\nstate: {}\ndifficulty: {}\nplayer: x={} y={} hp={}\n\
+             aliens: {}\nalien bullets: {}\nplayer bullets: {}\nbunkers: {}\n\n{}",
+            board.hud(),
+            self.tick,
+            self.state.name(),
+            board.difficulty.name(),
+            player.pos.x,
+            player.pos.y,
+            player.hp,
+            board.level.aliens.len(),
+            board.level.bullets.len(),
+            board.level.player_bullets.len(),
+            board.level.bunkers.len(),
+            FrameBuffer::render(board)
+        )
+    }
+
+    fn handle_event(&mut self, event: InputEvent) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(self.tick, &event) {
+                self.recording_error = Some(Error::Record(recorder.path().to_path_buf(), e));
+                self.recorder = None;
+            }
+        }
+        match event {
+            Event::Input(input) => self.process_input(input),
+            Event::MouseInput(me) => self.process_mouse_input(me),
+            Event::Release(input) => self.process_release(input),
+            Event::Pad { action, pressed } => self.process_pad(action, pressed),
+            Event::Resize(width, height) => self.resize(width, height),
+        }
+    }
+
+    /// The next frame adapts to the new size on its own.
+    fn resize(&mut self, width: u16, height: u16) {
+        self.too_small = !renderer::fits(width, height);
+        self.screen = Some((width, height));
+    }
+
+    /// Moves a mouse event from terminal cells onto playfield positions,
+    /// dropping it when it's outside the playfield. Recordings keep the
+    /// playfield positions so they don't depend on the terminal size.
+    fn playfield_mouse(&self, me: MouseEvent) -> Option<MouseEvent> {
+        let (width, height) = self.screen?;
+        // termion counts cells from one
+        let point = |x: u16, y: u16| {
+            let pos =
+                renderer::playfield_point(width, height, x.checked_sub(1)?, y.checked_sub(1)?)?;
+            Some((pos.x as u16, pos.y as u16))
+        };
+        let me = match me {
+            MouseEvent::Press(button, x, y) => {
+                let (x, y) = point(x, y)?;
+                MouseEvent::Press(button, x, y)
+            }
+            MouseEvent::Release(x, y) => {
+                let (x, y) = point(x, y)?;
+                MouseEvent::Release(x, y)
+            }
+            MouseEvent::Hold(x, y) => {
+                let (x, y) = point(x, y)?;
+                MouseEvent::Hold(x, y)
+            }
+        };
+        Some(me)
+    }
+
+    /// Pauses a game the terminal can't show. The pause goes through
+    /// `handle_event` so it ends up in recordings like a key press.
+    fn pause_if_hidden(&mut self) {
+        let playing = matches!(self.state, GameState::Playing);
+        if playing && self.too_small && self.replay.is_none() {
+            if let Some(&key) = self.bindings.keys(Action::Pause).first() {
+                self.handle_event(Event::Input(key));
+            }
+        }
+    }
+
+    /// Feeds the recorded inputs that were handled before the next tick.
+    fn play_replay(&mut self) {
+        while let Some(event) = self.replay.as_mut().and_then(|r| r.next_due(self.tick)) {
+            self.handle_event(event);
+        }
+    }
+
+    fn replay_live_input(&mut self, event: InputEvent) {
+        if let Event::Input(key) = event {
+            if key == Key::Esc || self.bindings.action(key) == Some(Action::Quit) {
+                self.is_exiting = true;
+            }
         }
     }
 
     fn process_input(&mut self, input: termion::event::Key) {
+        match &self.state {
+            GameState::Title => self.title_input(input),
+            GameState::Playing => self.playing_input(input),
+            GameState::Paused => self.paused_input(input),
+            GameState::LevelComplete => self.level_complete_input(input),
+            GameState::GameOver => self.game_over_input(input),
+            GameState::EnterInitials(_) => self.enter_initials_input(input),
+            GameState::HighScores(_) | GameState::Bindings => self.state = GameState::Title,
+        }
+    }
+
+    fn title_input(&mut self, input: Key) {
+        if self.bindings.action(input) == Some(Action::Quit) {
+            self.is_exiting = true;
+            return;
+        }
         match input {
-            Key::Esc => self.is_exiting = true,
-            Key::Char('q') => self.is_exiting = true,
-            Key::Right => self.board.move_player(Direction::Right),
-            Key::Left => self.board.move_player(Direction::Left),
-            Key::Down => self.board.move_player(Direction::None),
-            Key::Char(' ') => self.board.player_fire(),
-            Key::Char('n') => self.board.next_level(),
+            Key::Char('\n') | Key::Char(' ') => self.new_game(),
+            Key::Char('h') => self.state = GameState::HighScores(None),
+            Key::Char('k') => self.state = GameState::Bindings,
+            Key::Char('d') => self.board.difficulty = self.board.difficulty.next(),
+            _ => {}
+        }
+    }
+
+    fn playing_input(&mut self, input: Key) {
+        match self.bindings.action(input) {
+            Some(Action::MoveRight) => self.held_keys.press(Direction::Right, self.tick),
+            Some(Action::MoveLeft) => self.held_keys.press(Direction::Left, self.tick),
+            Some(action) => self.playing_action(action),
+            None => {}
+        }
+    }
 
+    /// Actions while playing other than moving, which keys and gamepads
+    /// each track on their own.
+    fn playing_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.is_exiting = true,
+            Action::Stop => self.stop_player(),
+            Action::Fire => self.board.player_fire(),
+            Action::Pause => self.state = GameState::Paused,
+            Action::MoveLeft | Action::MoveRight | Action::NextLevel => {}
+        }
+    }
+
+    fn paused_input(&mut self, input: Key) {
+        if let Some(action) = self.bindings.action(input) {
+            self.paused_action(action);
+        }
+    }
+
+    fn paused_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.is_exiting = true,
+            Action::Pause => self.state = GameState::Playing,
+            _ => {}
+        }
+    }
+
+    fn level_complete_input(&mut self, input: Key) {
+        if let Some(action) = self.bindings.action(input) {
+            self.level_complete_action(action);
+        }
+    }
+
+    fn level_complete_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.is_exiting = true,
+            Action::NextLevel => {
+                self.board.next_level();
+                self.state = GameState::Playing;
+                self.stop_player();
+            }
+            _ => {}
+        }
+    }
+
+    fn game_over_input(&mut self, input: Key) {
+        if let Some(action) = self.bindings.action(input) {
+            self.game_over_action(action);
+        }
+    }
+
+    fn game_over_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.is_exiting = true,
+            Action::NextLevel if self.highscores.qualifies(self.board.score) => {
+                self.state = GameState::EnterInitials(String::new());
+            }
+            Action::NextLevel => self.new_game(),
+            _ => {}
+        }
+    }
+
+    /// Gamepad buttons act like the keys bound to the same action, except
+    /// that fire starts a game from the title screen. Moves are tracked in
+    /// any state, like key releases.
+    fn process_pad(&mut self, action: Action, pressed: bool) {
+        let dir = match action {
+            Action::MoveLeft => Direction::Left,
+            Action::MoveRight => Direction::Right,
+            _ => Direction::None,
+        };
+        if dir != Direction::None {
+            if pressed {
+                self.pad_direction = dir;
+            } else if self.pad_direction == dir {
+                self.pad_direction = Direction::None;
+            }
+            return;
+        }
+        if !pressed {
+            return;
+        }
+        match &self.state {
+            GameState::Title if action == Action::Fire => self.new_game(),
+            GameState::Playing => self.playing_action(action),
+            GameState::Paused => self.paused_action(action),
+            GameState::LevelComplete => self.level_complete_action(action),
+            GameState::GameOver => self.game_over_action(action),
+            GameState::HighScores(_) | GameState::Bindings => self.state = GameState::Title,
+            GameState::Title | GameState::EnterInitials(_) => {}
+        }
+    }
+
+    fn enter_initials_input(&mut self, input: Key) {
+        let initials = match &mut self.state {
+            GameState::EnterInitials(initials) => initials,
+            _ => return,
+        };
+        match input {
+            Key::Char('\n') if !initials.is_empty() => {
+                let initials = initials.clone();
+                self.record_highscore(&initials);
+            }
+            Key::Char(c) if c.is_ascii_alphanumeric() && initials.len() < INITIALS_LEN => {
+                initials.push(c.to_ascii_uppercase());
+            }
+            Key::Backspace => {
+                initials.pop();
+            }
+            Key::Esc => self.state = GameState::Title,
+            _ => {}
+        }
+    }
+
+    fn record_highscore(&mut self, initials: &str) {
+        let entry = HighScore::new(initials, self.board.score, self.board.level.number);
+        let rank = self.highscores.insert(entry);
+        if let Some(path) = &self.highscores_path {
+            self.highscores_error = self
+                .highscores
+                .save(path)
+                .err()
+                .map(|e| format!("Could not save {}: {}", path.display(), e));
+        }
+        self.state = GameState::HighScores(rank);
+    }
+
+    /// Releases count in any state so keys let go during a pause aren't
+    /// still held when the game resumes.
+    fn process_release(&mut self, input: Key) {
+        match self.bindings.action(input) {
+            Some(Action::MoveRight) => self.held_keys.release(Direction::Right),
+            Some(Action::MoveLeft) => self.held_keys.release(Direction::Left),
             _ => {}
         }
     }
 
     fn process_mouse_input(&mut self, me: termion::event::MouseEvent) {
+        if !matches!(self.state, GameState::Playing) {
+            return;
+        }
         match me {
-            MouseEvent::Press(btn, x, y) => {
-                if btn == MouseButton::Left {
-                    if self.board.player.state.pos.x > x as i16 {
-                        self.board.move_player(Direction::Left)
-                    } else {
-                        self.board.move_player(Direction::Right)
-                    }
-                }
+            MouseEvent::Press(MouseButton::Left, x, _) | MouseEvent::Hold(x, _) => {
+                self.held_keys.clear();
+                self.held_direction = Direction::None;
+                self.board.move_player_to(x as i16);
+            }
+            MouseEvent::Press(MouseButton::Right | MouseButton::Middle, _, _) => {
+                self.board.player_fire()
             }
-            MouseEvent::Release(v1, v2) => self.board.move_player(Direction::None),
-
             _ => {}
         }
     }
 
     fn update(&mut self) {
-        self.board.update();
+        self.tick += 1;
+        if let GameState::Playing = self.state {
+            let dir = match self.pad_direction {
+                Direction::None => self.held_keys.direction(self.tick),
+                dir => dir,
+            };
+            if dir != self.held_direction {
+                self.held_direction = dir;
+                self.board.move_player(dir);
+            } else {
+                self.board.hold_player(dir);
+            }
+            self.board.update();
+            if self.board.game_over {
+                self.state = GameState::GameOver;
+            } else if self.board.level.is_finished() {
+                self.state = GameState::LevelComplete;
+            }
+        }
     }
 
-    fn render(&mut self) {
-        self.renderer.render(&self.board);
+    fn render(&mut self) -> io::Result<()> {
+        match &self.state {
+            GameState::Title => self.renderer.render_text("", &self.title_lines()),
+            GameState::Playing => self.renderer.render(&self.board, &self.board.hud()),
+            GameState::Paused | GameState::LevelComplete | GameState::GameOver => {
+                let lines = self.state.banner(&self.bindings);
+                let overlay = Overlay {
+                    background: &self.board,
+                    lines: &lines,
+                };
+                self.renderer.render(&overlay, &self.board.hud())
+            }
+            GameState::EnterInitials(initials) => {
+                let lines = self.initials_lines(initials);
+                self.renderer.render_text(&self.board.hud(), &lines)
+            }
+            GameState::HighScores(rank) => {
+                let lines = self.highscore_lines(*rank);
+                self.renderer.render_text("", &lines)
+            }
+            GameState::Bindings => {
+                let lines = self.bindings_lines();
+                self.renderer.render_text("", &lines)
+            }
+        }
+    }
+
+    fn title_lines(&self) -> Vec<(String, Color)> {
+        vec![
+            ("R U S T I V A N D E R S".to_string(), Color::LightGreen),
+            (String::new(), Color::Reset),
+            ("[Enter] Play".to_string(), Color::White),
+            ("[h] High scores".to_string(), Color::White),
+            ("[k] Key bindings".to_string(), Color::White),
+            (
+                format!("[d] Difficulty: {}", self.board.difficulty.label()),
+                Color::White,
+            ),
+            ("[q] Quit".to_string(), Color::White),
+        ]
+    }
+
+    fn initials_lines(&self, initials: &str) -> Vec<(String, Color)> {
+        vec![
+            ("NEW HIGH SCORE!".to_string(), Color::LightGreen),
+            (String::new(), Color::Reset),
+            (format!("Score: {}", self.board.score), Color::White),
+            (
+                format!("Your initials: {:_<1$}", initials, INITIALS_LEN),
+                Color::Yellow,
+            ),
+            (String::new(), Color::Reset),
+            ("[Enter] Save   [Esc] Skip".to_string(), Color::Gray),
+        ]
+    }
+
+    fn bindings_lines(&self) -> Vec<(String, Color)> {
+        let mut lines = vec![
+            ("KEY BINDINGS".to_string(), Color::LightGreen),
+            (String::new(), Color::Reset),
+        ];
+        for action in Action::ALL {
+            let keys = self
+                .bindings
+                .keys(action)
+                .into_iter()
+                .map(bindings::key_name)
+                .collect::<Vec<_>>();
+            lines.push((
+                format!("{:<12}{:<20}", action.label(), keys.join(", ")),
+                Color::White,
+            ));
+        }
+        if let Some(path) = &self.config_path {
+            lines.push((String::new(), Color::Reset));
+            lines.push((format!("Set in {}", path.display()), Color::Gray));
+        }
+        lines.push((String::new(), Color::Reset));
+        lines.push(("Press any key".to_string(), Color::Gray));
+        lines
+    }
+
+    fn highscore_lines(&self, highlight: Option<usize>) -> Vec<(String, Color)> {
+        let mut lines = vec![
+            ("HIGH SCORES".to_string(), Color::LightGreen),
+            (String::new(), Color::Reset),
+        ];
+        if self.highscores.entries.is_empty() {
+            lines.push(("No scores yet".to_string(), Color::Gray));
+        }
+        for (rank, entry) in self.highscores.entries.iter().enumerate() {
+            let color = if Some(rank) == highlight {
+                Color::Yellow
+            } else {
+                Color::White
+            };
+            lines.push((
+                format!(
+                    "{:>2}. {:<3}  {:>6}  Level {:<3} {}",
+                    rank + 1,
+                    entry.initials,
+                    entry.score,
+                    entry.level,
+                    entry.date
+                ),
+                color,
+            ));
+        }
+        if let Some(error) = &self.highscores_error {
+            lines.push((String::new(), Color::Reset));
+            lines.push((error.clone(), Color::LightRed));
+        }
+        lines.push((String::new(), Color::Reset));
+        lines.push(("Press any key".to_string(), Color::Gray));
+        lines
     }
 }
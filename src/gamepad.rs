@@ -0,0 +1,357 @@
+//! Gamepads and joysticks read straight from the Linux evdev devices in
+//! `/dev/input`. Sticks, hats and buttons are mapped onto the same actions
+//! the keyboard drives. Reading the devices usually needs membership in the
+//! `input` group, devices that can't be opened are skipped.
+
+use crate::bindings::Action;
+use crate::events::{Event, Message};
+
+use rustivanders::util::Direction;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::thread::{self, JoinHandle};
+
+/// Size of a `struct input_event`: a timestamp followed by the event type,
+/// code and value. The timestamp's size depends on the target.
+pub const EVENT_SIZE: usize = std::mem::size_of::<libc::input_event>();
+
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+
+const ABS_X: u16 = 0x00;
+const ABS_HAT0X: u16 = 0x10;
+
+const BTN_JOYSTICK: u16 = 0x120;
+const BTN_GAMEPAD: u16 = 0x130;
+const BTN_SELECT: u16 = 0x13a;
+const BTN_START: u16 = 0x13b;
+const BTN_MODE: u16 = 0x13c;
+const BTN_DPAD_LEFT: u16 = 0x222;
+const BTN_DPAD_RIGHT: u16 = 0x223;
+
+/// One record read from an event device.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RawEvent {
+    pub kind: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+impl RawEvent {
+    pub fn parse(record: &[u8; EVENT_SIZE]) -> RawEvent {
+        // SAFETY: `record` holds exactly one input_event, which is plain
+        // data that any bytes are valid for
+        let event =
+            unsafe { std::ptr::read_unaligned(record.as_ptr().cast::<libc::input_event>()) };
+        RawEvent {
+            kind: event.type_,
+            code: event.code,
+            value: event.value,
+        }
+    }
+}
+
+/// Turns the raw events of one device into action presses and releases.
+///
+/// The d-pad, the hat and the left stick all move the ship, the first of
+/// them that is pushed wins. Start pauses, select moves on to the next
+/// level and every other button fires.
+pub struct Mapper {
+    /// Minimum and maximum reported by the stick's x axis.
+    stick_range: (i32, i32),
+    stick: Direction,
+    hat: Direction,
+    dpad_left: bool,
+    dpad_right: bool,
+    direction: Direction,
+}
+
+impl Mapper {
+    pub fn new(stick_range: (i32, i32)) -> Self {
+        Mapper {
+            stick_range,
+            stick: Direction::None,
+            hat: Direction::None,
+            dpad_left: false,
+            dpad_right: false,
+            direction: Direction::None,
+        }
+    }
+
+    pub fn handle(&mut self, event: RawEvent) -> Vec<(Action, bool)> {
+        match (event.kind, event.code) {
+            (EV_ABS, ABS_X) => self.stick = self.stick_direction(event.value),
+            (EV_ABS, ABS_HAT0X) => self.hat = direction(event.value.signum()),
+            // 2 is an autorepeat of a held button
+            (EV_KEY, _) if event.value == 2 => {}
+            (EV_KEY, BTN_DPAD_LEFT) => self.dpad_left = event.value != 0,
+            (EV_KEY, BTN_DPAD_RIGHT) => self.dpad_right = event.value != 0,
+            (EV_KEY, code) => {
+                return button(code)
+                    .map(|action| vec![(action, event.value != 0)])
+                    .unwrap_or_default()
+            }
+            _ => {}
+        }
+        self.move_actions()
+    }
+
+    fn stick_direction(&self, value: i32) -> Direction {
+        let (min, max) = self.stick_range;
+        let center = min + (max - min) / 2;
+        let dead_zone = (max - min) / 4;
+        direction((value - center) / dead_zone.max(1))
+    }
+
+    /// Releases the old and presses the new move action when the combined
+    /// direction changed.
+    fn move_actions(&mut self) -> Vec<(Action, bool)> {
+        let dpad = match (self.dpad_left, self.dpad_right) {
+            (true, false) => Direction::Left,
+            (false, true) => Direction::Right,
+            _ => Direction::None,
+        };
+        let dir = [dpad, self.hat, self.stick]
+            .into_iter()
+            .find(|dir| *dir != Direction::None)
+            .unwrap_or(Direction::None);
+        if dir == self.direction {
+            return Vec::new();
+        }
+        let old = std::mem::replace(&mut self.direction, dir);
+        move_action(old)
+            .map(|action| (action, false))
+            .into_iter()
+            .chain(move_action(dir).map(|action| (action, true)))
+            .collect()
+    }
+}
+
+fn direction(sign: i32) -> Direction {
+    match sign {
+        s if s < 0 => Direction::Left,
+        s if s > 0 => Direction::Right,
+        _ => Direction::None,
+    }
+}
+
+fn move_action(dir: Direction) -> Option<Action> {
+    match dir {
+        Direction::Left => Some(Action::MoveLeft),
+        Direction::Right => Some(Action::MoveRight),
+        _ => None,
+    }
+}
+
+fn button(code: u16) -> Option<Action> {
+    match code {
+        BTN_START => Some(Action::Pause),
+        BTN_SELECT => Some(Action::NextLevel),
+        BTN_MODE => None,
+        BTN_JOYSTICK..=0x13e => Some(Action::Fire),
+        _ => None,
+    }
+}
+
+/// Starts a reader thread for every gamepad or joystick found.
+pub fn spawn(tx: Sender<Message>) -> Vec<JoinHandle<()>> {
+    gamepads()
+        .into_iter()
+        .filter_map(|path| File::open(path).ok())
+        .map(|device| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let mut mapper = Mapper::new(stick_range(&device).unwrap_or((-32768, 32767)));
+                let _ = read_events(device, &mut mapper, |action, pressed| {
+                    tx.send(Ok(Event::Pad { action, pressed })).is_ok()
+                });
+            })
+        })
+        .collect()
+}
+
+/// Feeds every record of `device` through `mapper` until the device goes
+/// away or `send` returns false.
+pub fn read_events<R: Read>(
+    mut device: R,
+    mapper: &mut Mapper,
+    mut send: impl FnMut(Action, bool) -> bool,
+) -> io::Result<()> {
+    let mut record = [0; EVENT_SIZE];
+    loop {
+        device.read_exact(&mut record)?;
+        for (action, pressed) in mapper.handle(RawEvent::parse(&record)) {
+            if !send(action, pressed) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Event devices announcing gamepad or joystick buttons in sysfs.
+fn gamepads() -> Vec<PathBuf> {
+    let mut paths = fs::read_dir("/sys/class/input")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+        .filter(|entry| {
+            let caps = entry.path().join("device/capabilities/key");
+            fs::read_to_string(caps).is_ok_and(|bits| {
+                has_capability(&bits, BTN_GAMEPAD) || has_capability(&bits, BTN_JOYSTICK)
+            })
+        })
+        .map(|entry| Path::new("/dev/input").join(entry.file_name()))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// Whether bit `code` is set in a sysfs capability bitmap, hex words with
+/// the lowest bits last.
+fn has_capability(bitmap: &str, code: u16) -> bool {
+    let words = bitmap.split_whitespace().rev().collect::<Vec<_>>();
+    let bits = usize::BITS as usize;
+    words
+        .get(code as usize / bits)
+        .and_then(|word| usize::from_str_radix(word, 16).ok())
+        .is_some_and(|word| word & (1 << (code as usize % bits)) != 0)
+}
+
+/// Range of the x axis as reported by the `EVIOCGABS` ioctl.
+fn stick_range(device: &File) -> Option<(i32, i32)> {
+    // struct input_absinfo: value, minimum, maximum, fuzz, flat, resolution
+    let mut info = [0i32; 6];
+    // _IOR('E', 0x40 + ABS_X, struct input_absinfo), the request type is
+    // unsigned on glibc but signed on musl and Android
+    let request = (0x8018_4540 + ABS_X as u32) as libc::Ioctl;
+    // SAFETY: the request writes exactly one input_absinfo into `info`
+    let result = unsafe { libc::ioctl(device.as_raw_fd(), request, info.as_mut_ptr()) };
+    (result == 0 && info[1] < info[2]).then_some((info[1], info[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(kind: u16, code: u16, value: i32) -> Vec<u8> {
+        // The timestamp comes first and is left at zero
+        let mut bytes = vec![0; EVENT_SIZE - 8];
+        bytes.extend(kind.to_ne_bytes());
+        bytes.extend(code.to_ne_bytes());
+        bytes.extend(value.to_ne_bytes());
+        bytes
+    }
+
+    /// Plays a recorded stream of `(type, code, value)` records.
+    fn play(stream: &[(u16, u16, i32)], mapper: &mut Mapper) -> Vec<(Action, bool)> {
+        let bytes = stream
+            .iter()
+            .flat_map(|(kind, code, value)| record(*kind, *code, *value))
+            .collect::<Vec<_>>();
+        let mut actions = Vec::new();
+        let result = read_events(bytes.as_slice(), mapper, |action, pressed| {
+            actions.push((action, pressed));
+            true
+        });
+        assert_eq!(
+            result.unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof,
+            "the stream ends with the device"
+        );
+        actions
+    }
+
+    #[test]
+    fn stick_moves_past_the_dead_zone() {
+        let mut mapper = Mapper::new((0, 255));
+
+        let actions = play(
+            &[
+                (EV_ABS, ABS_X, 140),
+                (EV_ABS, ABS_X, 10),
+                (EV_ABS, ABS_X, 250),
+                (EV_ABS, ABS_X, 128),
+            ],
+            &mut mapper,
+        );
+
+        assert_eq!(
+            actions,
+            vec![
+                (Action::MoveLeft, true),
+                (Action::MoveLeft, false),
+                (Action::MoveRight, true),
+                (Action::MoveRight, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn dpad_wins_over_the_stick() {
+        let mut mapper = Mapper::new((-32768, 32767));
+
+        let actions = play(
+            &[
+                (EV_ABS, ABS_X, 30000),
+                (EV_KEY, BTN_DPAD_LEFT, 1),
+                (EV_KEY, BTN_DPAD_LEFT, 2),
+                (EV_KEY, BTN_DPAD_LEFT, 0),
+                (EV_ABS, ABS_HAT0X, -1),
+            ],
+            &mut mapper,
+        );
+
+        assert_eq!(
+            actions,
+            vec![
+                (Action::MoveRight, true),
+                (Action::MoveRight, false),
+                (Action::MoveLeft, true),
+                (Action::MoveLeft, false),
+                (Action::MoveRight, true),
+                (Action::MoveRight, false),
+                (Action::MoveLeft, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn buttons_fire_and_pause() {
+        let mut mapper = Mapper::new((-32768, 32767));
+
+        let actions = play(
+            &[
+                (EV_KEY, BTN_GAMEPAD, 1),
+                (EV_KEY, BTN_GAMEPAD, 0),
+                (EV_KEY, BTN_JOYSTICK, 1),
+                (EV_KEY, BTN_START, 1),
+                (EV_KEY, BTN_MODE, 1),
+                (EV_KEY, 0x1e, 1),
+            ],
+            &mut mapper,
+        );
+
+        assert_eq!(
+            actions,
+            vec![
+                (Action::Fire, true),
+                (Action::Fire, false),
+                (Action::Fire, true),
+                (Action::Pause, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn capability_bitmaps() {
+        let pad = "7fdb000000000000 0 0 0 0";
+        assert!(has_capability(pad, BTN_GAMEPAD));
+        assert!(!has_capability(pad, BTN_JOYSTICK));
+        let keyboard = "fffffffffffffffe";
+        assert!(!has_capability(keyboard, BTN_GAMEPAD));
+    }
+}
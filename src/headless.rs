@@ -0,0 +1,255 @@
+use crate::board::Board;
+use crate::util::Direction;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// What an input source can ask of the player.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Input {
+    Move(Direction),
+    Fire,
+}
+
+/// Drives the player when the game runs without a terminal.
+pub trait InputSource {
+    /// Inputs to apply before simulating the step after `tick`.
+    fn inputs(&mut self, tick: u64, board: &Board) -> Vec<Input>;
+}
+
+/// Final state of a headless run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub seed: u64,
+    pub ticks: u64,
+    pub score: u32,
+    pub level: u16,
+    pub aliens: usize,
+    pub lives: u16,
+    pub game_over: bool,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "seed={}", self.seed)?;
+        writeln!(f, "ticks={}", self.ticks)?;
+        writeln!(f, "score={}", self.score)?;
+        writeln!(f, "level={}", self.level)?;
+        writeln!(f, "aliens={}", self.aliens)?;
+        writeln!(f, "lives={}", self.lives)?;
+        write!(f, "game_over={}", self.game_over)
+    }
+}
+
+/// Simulates up to `ticks` steps of `board`, moving on to the next level
+/// whenever one is cleared. Stops early when the game is over.
+pub fn run(board: &mut Board, ticks: u64, input: &mut dyn InputSource) -> Outcome {
+    let mut tick = 0;
+    while tick < ticks && !board.game_over {
+        if board.level.is_finished() {
+            board.next_level();
+        }
+        for input in input.inputs(tick, board) {
+            match input {
+                Input::Move(dir) => board.move_player(dir),
+                Input::Fire => board.player_fire(),
+            }
+        }
+        board.update();
+        tick += 1;
+    }
+
+    Outcome {
+        seed: board.seed,
+        ticks: tick,
+        score: board.score,
+        level: board.level.number,
+        aliens: board.level.aliens.len(),
+        lives: board.player.state.hp,
+        game_over: board.game_over,
+    }
+}
+
+/// Chases the lowest alien and fires whenever it is lined up.
+pub struct Autopilot;
+
+impl InputSource for Autopilot {
+    fn inputs(&mut self, _: u64, board: &Board) -> Vec<Input> {
+        let target = board
+            .level
+            .aliens
+            .values()
+            .map(|alien| alien.state())
+            .min_by_key(|state| state.bottom())
+            .map(|state| state.pos.x + state.width() / 2);
+        let x = board.player.state.fire_point().x;
+        match target {
+            Some(target) if target < x => vec![Input::Move(Direction::Left)],
+            Some(target) if target > x => vec![Input::Move(Direction::Right)],
+            Some(_) => vec![Input::Move(Direction::None), Input::Fire],
+            None => vec![Input::Move(Direction::None)],
+        }
+    }
+}
+
+/// Inputs read from a script of `<tick> left|right|stop|fire` lines.
+pub struct Script {
+    inputs: Vec<(u64, Input)>,
+    next: usize,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Script, ScriptError> {
+        let file = path.display().to_string();
+        let src =
+            fs::read_to_string(path).map_err(|e| ScriptError::new(&file, None, e.to_string()))?;
+        Script::parse(&file, &src)
+    }
+
+    pub fn parse(file: &str, src: &str) -> Result<Script, ScriptError> {
+        let mut inputs = Vec::new();
+        let mut last_tick = 0;
+        for (idx, raw) in src.lines().enumerate() {
+            let error = |message: String| ScriptError::new(file, Some(idx + 1), message);
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (tick, input) = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [tick, input] => (*tick, *input),
+                _ => return Err(error(format!("expected '<tick> <input>', got '{}'", line))),
+            };
+            let tick: u64 = tick
+                .parse()
+                .map_err(|_| error(format!("invalid tick '{}'", tick)))?;
+            if tick < last_tick {
+                return Err(error(format!("tick {} is before tick {}", tick, last_tick)));
+            }
+            let input = match input {
+                "left" => Input::Move(Direction::Left),
+                "right" => Input::Move(Direction::Right),
+                "stop" => Input::Move(Direction::None),
+                "fire" => Input::Fire,
+                _ => return Err(error(format!("unknown input '{}'", input))),
+            };
+            last_tick = tick;
+            inputs.push((tick, input));
+        }
+        Ok(Script { inputs, next: 0 })
+    }
+}
+
+impl InputSource for Script {
+    fn inputs(&mut self, tick: u64, _: &Board) -> Vec<Input> {
+        let due = self.inputs[self.next..]
+            .iter()
+            .take_while(|(at, _)| *at <= tick)
+            .map(|(_, input)| *input)
+            .collect::<Vec<_>>();
+        self.next += due.len();
+        due
+    }
+}
+
+#[derive(Debug)]
+pub struct ScriptError {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ScriptError {
+    fn new(file: &str, line: Option<usize>, message: String) -> Self {
+        ScriptError {
+            file: file.to_string(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::DEFAULT_LIVES;
+    use crate::level_file;
+    use crate::sprite::Assets;
+
+    fn board(seed: u64) -> Board {
+        let assets = Assets::builtin();
+        let levels = level_file::builtin_levels(&assets);
+        Board::new(levels, assets, DEFAULT_LIVES, seed)
+    }
+
+    #[test]
+    fn autopilot_run_is_reproducible() {
+        let outcome = run(&mut board(1234), 3000, &mut Autopilot);
+
+        assert_eq!(
+            outcome,
+            Outcome {
+                seed: 1234,
+                ticks: 2663,
+                score: 930,
+                level: 3,
+                aliens: 5,
+                lives: 0,
+                game_over: true,
+            }
+        );
+        assert_eq!(run(&mut board(1234), 3000, &mut Autopilot), outcome);
+    }
+
+    #[test]
+    fn scripted_run_is_reproducible() {
+        let src = "0 left\n30 fire\n60 right\n90 fire  # second shot\n120 stop\n150 fire\n";
+        let mut script = Script::parse("test", src).unwrap();
+
+        let outcome = run(&mut board(1234), 3000, &mut script);
+
+        assert_eq!(
+            outcome,
+            Outcome {
+                seed: 1234,
+                ticks: 3000,
+                score: 20,
+                level: 1,
+                aliens: 25,
+                lives: 3,
+                game_over: false,
+            }
+        );
+    }
+
+    #[test]
+    fn script_errors_report_their_line_number() {
+        let cases = [
+            ("0 left\n\n# comment\n5 jump\n", 4, "unknown input 'jump'"),
+            ("10 left\n5 right\n", 2, "tick 5 is before tick 10"),
+            ("soon fire\n", 1, "invalid tick 'soon'"),
+            ("0 left\n1\n", 2, "expected '<tick> <input>'"),
+        ];
+        for (src, line, message) in cases {
+            let error = Script::parse("test.txt", src).err().unwrap();
+
+            assert_eq!(error.line, Some(line), "{}", src);
+            assert!(error.message.contains(message), "{}", error);
+            assert!(error
+                .to_string()
+                .starts_with(&format!("test.txt:{}:", line)));
+        }
+    }
+}
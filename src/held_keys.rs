@@ -0,0 +1,158 @@
+use crate::game::TICK_RATE;
+
+use rustivanders::util::Direction;
+use std::time::Duration;
+
+/// How long a key counts as held after it went down. Terminals start to
+/// repeat a held key after a delay of around half a second, a key that
+/// isn't repeated by then has been let go.
+const FIRST_REPEAT_TIMEOUT: Duration = Duration::from_millis(600);
+
+/// How long a key counts as held after its last repeat.
+const REPEAT_TIMEOUT: Duration = Duration::from_millis(150);
+
+#[derive(Debug, Copy, Clone)]
+struct Held {
+    dir: Direction,
+    pressed_at: u64,
+    repeated_at: Option<u64>,
+}
+
+/// Tracks which movement keys are held down.
+///
+/// Terminals speaking the kitty keyboard protocol report key releases and
+/// a key is held until its release arrives. Others only send a key again
+/// while it is held, so there a key is let go once its repeats stop coming.
+/// Everything is counted in ticks, which keeps replays deterministic.
+#[derive(Debug)]
+pub struct HeldKeys {
+    /// Most recently pressed last.
+    keys: Vec<Held>,
+    /// Set once the terminal reported a release.
+    releases: bool,
+    /// Length of a tick, to turn the timeouts into ticks.
+    tick_rate: Duration,
+}
+
+impl Default for HeldKeys {
+    fn default() -> Self {
+        HeldKeys::new(TICK_RATE)
+    }
+}
+
+impl HeldKeys {
+    pub fn new(tick_rate: Duration) -> Self {
+        HeldKeys {
+            keys: Vec::new(),
+            releases: false,
+            tick_rate,
+        }
+    }
+
+    fn ticks(&self, timeout: Duration) -> u64 {
+        (timeout.as_millis() / self.tick_rate.as_millis().max(1)) as u64
+    }
+
+    /// `dir` went down, or was repeated, at `tick`.
+    pub fn press(&mut self, dir: Direction, tick: u64) {
+        let held = match self.keys.iter().position(|held| held.dir == dir) {
+            Some(idx) => Held {
+                repeated_at: Some(tick),
+                ..self.keys.remove(idx)
+            },
+            None => Held {
+                dir,
+                pressed_at: tick,
+                repeated_at: None,
+            },
+        };
+        self.keys.push(held);
+    }
+
+    pub fn release(&mut self, dir: Direction) {
+        self.releases = true;
+        self.keys.retain(|held| held.dir != dir);
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Where the held keys move the player at `tick`, the latest pressed
+    /// key wins.
+    pub fn direction(&mut self, tick: u64) -> Direction {
+        if !self.releases {
+            let first_repeat = self.ticks(FIRST_REPEAT_TIMEOUT);
+            let repeat = self.ticks(REPEAT_TIMEOUT);
+            self.keys.retain(|held| match held.repeated_at {
+                Some(at) => tick <= at + repeat,
+                None => tick <= held.pressed_at + first_repeat,
+            });
+        }
+        self.keys.last().map_or(Direction::None, |held| held.dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_tap_times_out_without_repeats() {
+        let mut keys = HeldKeys::default();
+
+        keys.press(Direction::Left, 10);
+
+        let timeout = keys.ticks(FIRST_REPEAT_TIMEOUT);
+        assert_eq!(keys.direction(10 + timeout), Direction::Left);
+        assert_eq!(keys.direction(11 + timeout), Direction::None);
+    }
+
+    #[test]
+    fn repeats_keep_a_key_held() {
+        let mut keys = HeldKeys::default();
+
+        keys.press(Direction::Right, 0);
+        keys.press(Direction::Right, 30);
+        keys.press(Direction::Right, 33);
+
+        let timeout = keys.ticks(REPEAT_TIMEOUT);
+        assert_eq!(keys.direction(33 + timeout), Direction::Right);
+        assert_eq!(keys.direction(34 + timeout), Direction::None);
+    }
+
+    #[test]
+    fn timeouts_follow_the_tick_rate() {
+        let mut keys = HeldKeys::new(Duration::from_millis(32));
+
+        keys.press(Direction::Left, 0);
+
+        assert_eq!(keys.direction(18), Direction::Left);
+        assert_eq!(keys.direction(19), Direction::None);
+    }
+
+    #[test]
+    fn releases_replace_the_timeouts() {
+        let mut keys = HeldKeys::default();
+        keys.release(Direction::Left);
+
+        keys.press(Direction::Left, 0);
+        assert_eq!(keys.direction(1000), Direction::Left);
+
+        keys.release(Direction::Left);
+        assert_eq!(keys.direction(1001), Direction::None);
+    }
+
+    #[test]
+    fn the_latest_key_wins() {
+        let mut keys = HeldKeys::default();
+        keys.release(Direction::None);
+
+        keys.press(Direction::Left, 0);
+        keys.press(Direction::Right, 5);
+        assert_eq!(keys.direction(6), Direction::Right);
+
+        keys.release(Direction::Right);
+        assert_eq!(keys.direction(7), Direction::Left);
+    }
+}
@@ -0,0 +1,281 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MAX_ENTRIES: usize = 10;
+pub const INITIALS_LEN: usize = 3;
+
+const APP_DIR: &str = "rustivanders";
+const FILE_NAME: &str = "highscores";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: u32,
+    pub level: u16,
+    pub date: String,
+}
+
+impl HighScore {
+    pub fn new(initials: &str, score: u32, level: u16) -> Self {
+        HighScore {
+            initials: initials.to_string(),
+            score,
+            level,
+            date: today(),
+        }
+    }
+
+    fn parse(line: &str) -> Option<HighScore> {
+        let fields = line.split('\t').collect::<Vec<_>>();
+        match fields.as_slice() {
+            [initials, score, level, date] => {
+                let valid_initials = !initials.is_empty()
+                    && initials.chars().count() <= INITIALS_LEN
+                    && initials.chars().all(|c| c.is_ascii_alphanumeric());
+                if !valid_initials {
+                    return None;
+                }
+                Some(HighScore {
+                    initials: initials.to_string(),
+                    score: score.parse().ok()?,
+                    level: level.parse().ok()?,
+                    date: date.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.initials, self.score, self.level, self.date
+        )
+    }
+}
+
+/// The best `MAX_ENTRIES` runs, highest score first.
+#[derive(Debug, Clone, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Reads the table from `path`. A missing file is an empty table and
+    /// malformed lines are skipped, so only I/O failures are reported.
+    pub fn load(path: &Path) -> io::Result<HighScores> {
+        let src = match fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HighScores::default()),
+            Err(e) => return Err(e),
+        };
+
+        let mut table = HighScores::default();
+        for entry in src.lines().filter_map(HighScore::parse) {
+            table.insert(entry);
+        }
+        Ok(table)
+    }
+
+    /// Writes the table to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut contents = String::new();
+        for entry in &self.entries {
+            contents.push_str(&entry.to_line());
+            contents.push('\n');
+        }
+        // Write next to the target and rename so a crash never leaves a half written table
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// A table holding just `scores`, for replays: a score qualifies
+    /// exactly when it did against the table the session was recorded with.
+    pub fn from_scores(scores: &[u32]) -> HighScores {
+        let mut table = HighScores::default();
+        for &score in scores {
+            table.insert(HighScore {
+                initials: "---".to_string(),
+                score,
+                level: 0,
+                date: String::new(),
+            });
+        }
+        table
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    /// Adds `entry` in score order and returns its rank, or `None` if it did
+    /// not make the table.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+/// `$XDG_DATA_HOME/rustivanders/highscores`, falling back to
+/// `~/.local/share` as the XDG base directory spec requires.
+pub fn default_path() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+    Some(data_home.join(APP_DIR).join(FILE_NAME))
+}
+
+/// Current UTC date as `YYYY-MM-DD`.
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts days since 1970-01-01 into a proleptic Gregorian date,
+/// see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(initials: &str, score: u32) -> HighScore {
+        HighScore {
+            initials: initials.to_string(),
+            score,
+            level: 1,
+            date: "2026-01-01".to_string(),
+        }
+    }
+
+    fn full_table() -> HighScores {
+        let mut table = HighScores::default();
+        for score in (1..=MAX_ENTRIES as u32).map(|n| n * 100) {
+            table.insert(entry("AAA", score));
+        }
+        table
+    }
+
+    #[test]
+    fn insert_keeps_score_order_and_returns_the_rank() {
+        let mut table = HighScores::default();
+
+        assert_eq!(table.insert(entry("B", 200)), Some(0));
+        assert_eq!(table.insert(entry("A", 300)), Some(0));
+        assert_eq!(table.insert(entry("C", 100)), Some(2));
+        // Ties rank below the scores already in the table
+        assert_eq!(table.insert(entry("D", 200)), Some(2));
+
+        let initials = table
+            .entries
+            .iter()
+            .map(|e| e.initials.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(initials, vec!["A", "B", "D", "C"]);
+    }
+
+    #[test]
+    fn insert_truncates_at_max_entries() {
+        let mut table = full_table();
+
+        assert_eq!(table.insert(entry("NEW", 550)), Some(5));
+        assert_eq!(table.entries.len(), MAX_ENTRIES);
+        assert_eq!(table.entries.last().unwrap().score, 200);
+        assert_eq!(table.insert(entry("LOW", 50)), None);
+        assert_eq!(table.entries.len(), MAX_ENTRIES);
+    }
+
+    #[test]
+    fn qualifies() {
+        assert!(!HighScores::default().qualifies(0));
+        assert!(HighScores::default().qualifies(1));
+
+        let table = full_table();
+        assert!(!table.qualifies(0));
+        assert!(!table.qualifies(99));
+        // Tying the lowest entry doesn't beat it
+        assert!(!table.qualifies(100));
+        assert!(table.qualifies(101));
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir =
+            std::env::temp_dir().join(format!("rustivanders-highscores-{}", std::process::id()));
+        let path = dir.join("nested").join(FILE_NAME);
+        let mut table = HighScores::default();
+        table.insert(entry("ABC", 1200));
+        table.insert(HighScore {
+            level: 4,
+            ..entry("Z9", 800)
+        });
+
+        table.save(&path).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(
+            &path,
+            format!("{}bad line\nA B\t1\t1\t2026-01-01\n", contents),
+        )
+        .unwrap();
+        let loaded = HighScores::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            contents,
+            "ABC\t1200\t1\t2026-01-01\nZ9\t800\t4\t2026-01-01\n"
+        );
+        assert_eq!(loaded.entries, table.entries);
+    }
+
+    #[test]
+    fn missing_table_is_empty() {
+        let path = std::env::temp_dir()
+            .join("rustivanders-no-such-dir")
+            .join(FILE_NAME);
+
+        assert!(HighScores::load(&path).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn civil_from_days_known_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(20_742), (2026, 10, 16));
+    }
+}
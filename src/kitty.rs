@@ -0,0 +1,129 @@
+//! Key events of the kitty keyboard protocol.
+//!
+//! With the "report event types" enhancement (flag 2) enabled, terminals
+//! supporting the protocol report key repeats and releases as CSI sequences
+//! like `ESC [ 1 ; 1 : 3 D` (Left released) or `ESC [ 97 ; 1 : 3 u` ('a'
+//! released). Presses keep their legacy encoding. termion doesn't know these
+//! sequences and hands them over as `Event::Unsupported`, they are parsed
+//! here. Terminals without support ignore the request and never send them.
+//!
+//! See https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+
+use termion::event::Key;
+
+/// Pushes the "report event types" flag onto the terminal's flag stack.
+pub const ENABLE: &str = "\x1b[>2u";
+
+/// Pops the flags pushed by `ENABLE`.
+pub const DISABLE: &str = "\x1b[<u";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum KeyEventKind {
+    Press,
+    Repeat,
+    Release,
+}
+
+/// Parses a kitty key event from the raw bytes of a CSI sequence.
+pub fn parse(bytes: &[u8]) -> Option<(Key, KeyEventKind)> {
+    let seq = std::str::from_utf8(bytes.strip_prefix(b"\x1b[")?).ok()?;
+    let final_byte = seq.chars().last()?;
+    let params = seq[..seq.len() - final_byte.len_utf8()]
+        .split(';')
+        .collect::<Vec<_>>();
+
+    // The key code may carry alternate keys after a ':', only the first matters
+    let code = params.first()?.split(':').next()?;
+    let code: u32 = if code.is_empty() {
+        1
+    } else {
+        code.parse().ok()?
+    };
+
+    // Modifiers and event type share a field, e.g. `1:3`. Both default to 1.
+    let kind = match params.get(1).and_then(|p| p.split(':').nth(1)) {
+        None | Some("1") => KeyEventKind::Press,
+        Some("2") => KeyEventKind::Repeat,
+        Some("3") => KeyEventKind::Release,
+        Some(_) => return None,
+    };
+
+    let key = match final_byte {
+        'u' => match code {
+            9 => Key::Char('\t'),
+            13 => Key::Char('\n'),
+            27 => Key::Esc,
+            127 => Key::Backspace,
+            c => Key::Char(char::from_u32(c)?),
+        },
+        '~' => match code {
+            2 => Key::Insert,
+            3 => Key::Delete,
+            5 => Key::PageUp,
+            6 => Key::PageDown,
+            7 => Key::Home,
+            8 => Key::End,
+            _ => return None,
+        },
+        'A' => Key::Up,
+        'B' => Key::Down,
+        'C' => Key::Right,
+        'D' => Key::Left,
+        'H' => Key::Home,
+        'F' => Key::End,
+        _ => return None,
+    };
+    Some((key, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_events() {
+        assert_eq!(
+            parse(b"\x1b[1;1:3D"),
+            Some((Key::Left, KeyEventKind::Release))
+        );
+        assert_eq!(
+            parse(b"\x1b[1;1:2C"),
+            Some((Key::Right, KeyEventKind::Repeat))
+        );
+        assert_eq!(parse(b"\x1b[1;1A"), Some((Key::Up, KeyEventKind::Press)));
+    }
+
+    #[test]
+    fn text_key_events() {
+        assert_eq!(
+            parse(b"\x1b[32;1:3u"),
+            Some((Key::Char(' '), KeyEventKind::Release))
+        );
+        assert_eq!(
+            parse(b"\x1b[97:65;2:3u"),
+            Some((Key::Char('a'), KeyEventKind::Release))
+        );
+        assert_eq!(
+            parse(b"\x1b[13;1:3u"),
+            Some((Key::Char('\n'), KeyEventKind::Release))
+        );
+    }
+
+    #[test]
+    fn tilde_key_events() {
+        assert_eq!(
+            parse(b"\x1b[3;1:3~"),
+            Some((Key::Delete, KeyEventKind::Release))
+        );
+        assert_eq!(parse(b"\x1b[42;1:3~"), None);
+    }
+
+    #[test]
+    fn other_sequences_are_ignored() {
+        assert_eq!(parse(b"\x1b[1;1:4D"), None);
+        assert_eq!(parse(b"\x1b[?2u"), None);
+        assert_eq!(parse(b"\x1b[1;1:3X"), None);
+        assert_eq!(parse(b"\x1bOP"), None);
+        assert_eq!(parse(b""), None);
+    }
+}
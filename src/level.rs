@@ -1,7 +1,13 @@
+use crate::ai::{BossAi, InvanderAi, MarchAi};
+use crate::difficulty::Difficulty;
+use crate::level_file::{AlienKind, LevelDef};
+use crate::sprite::Assets;
 use crate::sprite::Boss;
+use crate::sprite::Bunker;
 use crate::sprite::Invander;
 use crate::sprite::Sprite;
-use std::collections::HashMap;
+use crate::util::GameRng;
+use std::collections::BTreeMap;
 
 pub const SCREEN_WIDTH: usize = 94;
 pub const SCREEN_HEIGHT: usize = 30;
@@ -10,13 +16,16 @@ pub enum SpriteCategory {
     PlayerBullet,
     AlienBullet,
     Alien,
+    Bunker,
 }
 
 pub struct Level {
     pub number: u16,
-    pub player_bullets: HashMap<u32, Box<dyn for<'a> Sprite<'a>>>,
-    pub bullets: HashMap<u32, Box<dyn for<'a> Sprite<'a>>>,
-    pub aliens: HashMap<u32, Box<dyn for<'a> Sprite<'a>>>,
+    pub player_bullets: BTreeMap<u32, Box<dyn for<'a> Sprite<'a>>>,
+    pub bullets: BTreeMap<u32, Box<dyn for<'a> Sprite<'a>>>,
+    pub aliens: BTreeMap<u32, Box<dyn for<'a> Sprite<'a>>>,
+    pub bunkers: BTreeMap<u32, Box<dyn for<'a> Sprite<'a>>>,
+    pub march: Option<MarchAi>,
     pub entity_id_counter: u32,
 }
 
@@ -24,94 +33,80 @@ impl Level {
     pub fn new(number: u16) -> Self {
         Level {
             number,
-            player_bullets: HashMap::new(),
-            bullets: HashMap::new(),
-            aliens: HashMap::new(),
+            player_bullets: BTreeMap::new(),
+            bullets: BTreeMap::new(),
+            aliens: BTreeMap::new(),
+            bunkers: BTreeMap::new(),
+            march: None,
             entity_id_counter: 0,
         }
     }
 
-    pub fn one() -> Self {
-        let mut level = Level::new(1);
-
-        for row in 0..3 {
-            let n = 9;
-            for i in 0..n {
-                let x = 10 * (i) as i16 + 2;
-                let y = (SCREEN_HEIGHT - 2) as i16 - row * 5;
-                // let x_max = BOARD_WIDTH as i16 - x * (n - i);
-                let x_max = x + 7;
-                let x_range = (x, x_max as i16 - 2);
-                level.add_sprite(
-                    Box::new(Invander::new_fighter(x, y, &x_range)),
-                    SpriteCategory::Alien,
-                );
-            }
-        }
-
-        level
-    }
-
-    pub fn two() -> Self {
-        let mut level = Level::new(2);
-
-        for row in 0..3 {
-            let n = 9;
-            for i in 0..n {
-                let x = 10 * (i) as i16 + 2;
-                let y = (SCREEN_HEIGHT - 2) as i16 - row * 5;
-                // let x_max = BOARD_WIDTH as i16 - x * (n - i);
-                let x_max = x + 7;
-                let x_range = (x, x_max as i16 - 2);
-                if row == 2 {
-                    level.add_sprite(
-                        Box::new(Invander::new_tank(x, y, &x_range)),
-                        SpriteCategory::Alien,
-                    );
-                } else {
-                    level.add_sprite(
-                        Box::new(Invander::new_fighter(x, y, &x_range)),
-                        SpriteCategory::Alien,
+    pub fn from_def(
+        number: u16,
+        def: &LevelDef,
+        difficulty: Difficulty,
+        assets: &Assets,
+        rng: &mut GameRng,
+    ) -> Self {
+        let mut level = Level::new(number);
+
+        for alien in &def.aliens {
+            let (x, y) = (alien.pos.x, alien.pos.y);
+            let cells = assets.cells(&alien.sprite);
+            let hp = difficulty.hp(alien.hp);
+            let params = difficulty.ai(alien.ai);
+            let sprite: Box<dyn for<'a> Sprite<'a>> = match alien.kind {
+                AlienKind::Boss => Box::new(Boss::new(
+                    x,
+                    y,
+                    hp,
+                    alien.points,
+                    cells,
+                    BossAi::new(
+                        &alien.x_range,
+                        &alien.y_range,
+                        params.move_speed,
+                        params.fire_rate,
+                        params.bullet_speed,
+                        difficulty.boss_burst(),
+                        rng,
+                    ),
+                )),
+                _ => {
+                    let mut ai = InvanderAi::new(
+                        &alien.x_range,
+                        params.move_speed,
+                        params.fire_rate,
+                        params.bullet_speed,
+                        rng,
                     );
+                    ai.marching = def.march.is_some();
+                    Box::new(Invander::new(x, y, hp, alien.points, cells, ai))
                 }
-            }
+            };
+            level.add_sprite(sprite, SpriteCategory::Alien);
         }
 
-        level
-    }
-
-    pub fn three() -> Self {
-        let mut level = Level::new(3);
+        level.march = def
+            .march
+            .as_ref()
+            .map(|march| MarchAi::new(&difficulty.march(*march), level.aliens.len()));
 
-        for row in 0..5 {
-            let x = -10 * row;
-            let y = (SCREEN_HEIGHT - 2) as i16 - row * 5;
-            let x_max = SCREEN_WIDTH as i16 + (10 * row);
-            let x_range = (x, x_max as i16 - 2);
+        for bunker in &def.bunkers {
             level.add_sprite(
-                Box::new(Invander::new_assasin(x, y, &x_range)),
-                SpriteCategory::Alien,
+                Box::new(Bunker::new(
+                    bunker.pos.x,
+                    bunker.pos.y,
+                    assets.cells(&bunker.sprite),
+                )),
+                SpriteCategory::Bunker,
             );
         }
 
         level
     }
 
-    pub fn four() -> Self {
-        let mut level = Level::new(4);
-
-        let x = 32;
-        let y = 25;
-        let x_range = (-40, SCREEN_WIDTH as i16 + 20);
-        let y_range = (20, 30);
-        level.add_sprite(
-            Box::new(Boss::new_boss(x, y, &x_range, &y_range)),
-            SpriteCategory::Alien,
-        );
-
-        level
-    }
-
     pub fn is_finished(&self) -> bool {
         self.aliens.is_empty()
     }
@@ -138,6 +133,9 @@ impl Level {
             SpriteCategory::PlayerBullet => {
                 self.player_bullets.insert(id, sprite);
             }
+            SpriteCategory::Bunker => {
+                self.bunkers.insert(id, sprite);
+            }
         }
     }
 }
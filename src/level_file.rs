@@ -0,0 +1,797 @@
+use crate::sprite::Assets;
+use crate::util::Point;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Directory scanned for `*.lvl` files when the game starts.
+pub const LEVELS_DIR: &str = "levels";
+
+const LEVEL_FILE_EXTENSION: &str = "lvl";
+
+const BUILTIN_LEVELS: [(&str, &str); 5] = [
+    ("01_fighters.lvl", include_str!("../levels/01_fighters.lvl")),
+    ("02_tanks.lvl", include_str!("../levels/02_tanks.lvl")),
+    (
+        "03_assassins.lvl",
+        include_str!("../levels/03_assassins.lvl"),
+    ),
+    ("04_boss.lvl", include_str!("../levels/04_boss.lvl")),
+    ("05_invasion.lvl", include_str!("../levels/05_invasion.lvl")),
+];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AlienKind {
+    Fighter,
+    Tank,
+    Assassin,
+    Boss,
+}
+
+impl AlienKind {
+    fn parse(s: &str) -> Option<AlienKind> {
+        match s {
+            "fighter" => Some(AlienKind::Fighter),
+            "tank" => Some(AlienKind::Tank),
+            "assassin" => Some(AlienKind::Assassin),
+            "boss" => Some(AlienKind::Boss),
+            _ => None,
+        }
+    }
+
+    /// Name used in level files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlienKind::Fighter => "fighter",
+            AlienKind::Tank => "tank",
+            AlienKind::Assassin => "assassin",
+            AlienKind::Boss => "boss",
+        }
+    }
+
+    fn default_sprite(&self) -> &'static str {
+        self.name()
+    }
+
+    fn default_hp(&self) -> u16 {
+        match self {
+            AlienKind::Fighter => 1,
+            AlienKind::Tank => 2,
+            AlienKind::Assassin => 1,
+            AlienKind::Boss => 20,
+        }
+    }
+
+    fn default_points(&self) -> u32 {
+        match self {
+            AlienKind::Fighter => 10,
+            AlienKind::Tank => 20,
+            AlienKind::Assassin => 50,
+            AlienKind::Boss => 500,
+        }
+    }
+
+    fn default_ai(&self) -> AiParams {
+        match self {
+            AlienKind::Fighter => AiParams::new(10, 10.0, 8),
+            AlienKind::Tank => AiParams::new(4, 0.0, 1),
+            AlienKind::Assassin => AiParams::new(1, 0.8, 30),
+            AlienKind::Boss => AiParams::new(2, 1.0, 1),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AiParams {
+    pub move_speed: u16,
+    pub fire_rate: f64,
+    pub bullet_speed: i32,
+}
+
+impl AiParams {
+    pub fn new(move_speed: u16, fire_rate: f64, bullet_speed: i32) -> Self {
+        AiParams {
+            move_speed,
+            fire_rate,
+            bullet_speed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlienDef {
+    pub kind: AlienKind,
+    pub sprite: String,
+    pub pos: Point,
+    pub x_range: (i16, i16),
+    pub y_range: (i16, i16),
+    pub hp: u16,
+    pub points: u32,
+    pub ai: AiParams,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BunkerDef {
+    pub sprite: String,
+    pub pos: Point,
+}
+
+/// Makes the whole swarm move in lockstep instead of each alien on its own.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MarchDef {
+    /// Ticks between steps while the swarm is complete.
+    pub speed: u16,
+    /// Ticks between steps once the swarm has been thinned out.
+    pub min_speed: u16,
+    /// Rows descended when the swarm hits a screen edge.
+    pub step_down: i16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelDef {
+    pub aliens: Vec<AlienDef>,
+    pub bunkers: Vec<BunkerDef>,
+    pub march: Option<MarchDef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelFileError {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl LevelFileError {
+    fn new(file: &str, line: Option<usize>, message: String) -> Self {
+        LevelFileError {
+            file: file.to_string(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for LevelFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+impl std::error::Error for LevelFileError {}
+
+/// Levels shipped inside the binary, used when no levels directory is present.
+pub fn builtin_levels(assets: &Assets) -> Vec<LevelDef> {
+    BUILTIN_LEVELS
+        .iter()
+        .map(|(file, src)| {
+            parse_level(file, src, assets).unwrap_or_else(|e| panic!("invalid builtin level {}", e))
+        })
+        .collect()
+}
+
+/// Loads levels from `LEVELS_DIR` if it exists, otherwise falls back to the builtin set.
+pub fn load_default_levels(assets: &Assets) -> Result<Vec<LevelDef>, LevelFileError> {
+    let dir = Path::new(LEVELS_DIR);
+    if dir.is_dir() {
+        load_levels(dir, assets)
+    } else {
+        Ok(builtin_levels(assets))
+    }
+}
+
+/// Loads every `*.lvl` file from `dir`, ordered by file name.
+pub fn load_levels(dir: &Path, assets: &Assets) -> Result<Vec<LevelDef>, LevelFileError> {
+    let dir_name = dir.display().to_string();
+    let entries =
+        fs::read_dir(dir).map_err(|e| LevelFileError::new(&dir_name, None, e.to_string()))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| LevelFileError::new(&dir_name, None, e.to_string()))?
+            .path();
+        if path
+            .extension()
+            .is_some_and(|ext| ext == LEVEL_FILE_EXTENSION)
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    if paths.is_empty() {
+        return Err(LevelFileError::new(
+            &dir_name,
+            None,
+            format!("no .{} files found", LEVEL_FILE_EXTENSION),
+        ));
+    }
+
+    let mut levels = Vec::new();
+    for path in paths {
+        let file = path.display().to_string();
+        let src = fs::read_to_string(&path)
+            .map_err(|e| LevelFileError::new(&file, None, e.to_string()))?;
+        levels.push(parse_level(&file, &src, assets)?);
+    }
+    Ok(levels)
+}
+
+enum SectionKind {
+    Alien,
+    Formation,
+    Bunker,
+    March,
+}
+
+struct Section {
+    kind: SectionKind,
+    line: usize,
+    entries: Vec<(String, String, usize)>,
+}
+
+impl Section {
+    fn take(&mut self, key: &str) -> Option<(String, usize)> {
+        let idx = self.entries.iter().position(|(k, _, _)| k == key)?;
+        let (_, value, line) = self.entries.remove(idx);
+        Some((value, line))
+    }
+}
+
+/// Parses a level file. `file` is only used to label errors, sprite names are
+/// checked against `assets`.
+///
+/// The format is line based: `#` starts a comment, `[alien]`, `[formation]`,
+/// `[bunker]` and `[march]` open a new section and every other line is a `key = value` pair belonging to
+/// the current section.
+pub fn parse_level(file: &str, src: &str, assets: &Assets) -> Result<LevelDef, LevelFileError> {
+    let err = |line: usize, message: String| LevelFileError::new(file, Some(line), message);
+
+    let mut sections: Vec<Section> = Vec::new();
+    for (idx, raw) in src.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            if !line.ends_with(']') {
+                return Err(err(line_no, format!("malformed section header '{}'", line)));
+            }
+            let kind = match &line[1..line.len() - 1] {
+                "alien" => SectionKind::Alien,
+                "formation" => SectionKind::Formation,
+                "bunker" => SectionKind::Bunker,
+                "march" => SectionKind::March,
+                other => return Err(err(line_no, format!("unknown section '{}'", other))),
+            };
+            sections.push(Section {
+                kind,
+                line: line_no,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => {
+                return Err(err(
+                    line_no,
+                    format!("expected 'key = value', got '{}'", line),
+                ))
+            }
+        };
+        let section = match sections.last_mut() {
+            Some(section) => section,
+            None => return Err(err(line_no, format!("key '{}' outside of a section", key))),
+        };
+        if section.entries.iter().any(|(k, _, _)| k == key) {
+            return Err(err(line_no, format!("duplicate key '{}'", key)));
+        }
+        section
+            .entries
+            .push((key.to_string(), value.to_string(), line_no));
+    }
+
+    let mut aliens = Vec::new();
+    let mut bunkers = Vec::new();
+    let mut march = None;
+    for mut section in sections {
+        match section.kind {
+            SectionKind::Alien => {
+                aliens.push(parse_alien(&mut section, assets).map_err(|(l, m)| err(l, m))?)
+            }
+            SectionKind::Formation => {
+                aliens.extend(parse_formation(&mut section, assets).map_err(|(l, m)| err(l, m))?)
+            }
+            SectionKind::Bunker => {
+                bunkers.extend(parse_bunkers(&mut section, assets).map_err(|(l, m)| err(l, m))?)
+            }
+            SectionKind::March => {
+                if march.is_some() {
+                    return Err(err(
+                        section.line,
+                        "only one march section allowed".to_string(),
+                    ));
+                }
+                march = Some(parse_march(&mut section).map_err(|(l, m)| err(l, m))?)
+            }
+        }
+        if let Some((key, _, line)) = section.entries.first() {
+            return Err(err(*line, format!("unknown key '{}'", key)));
+        }
+    }
+
+    if aliens.is_empty() {
+        return Err(LevelFileError::new(
+            file,
+            None,
+            "level contains no aliens".to_string(),
+        ));
+    }
+
+    Ok(LevelDef {
+        aliens,
+        bunkers,
+        march,
+    })
+}
+
+type SectionResult<T> = Result<T, (usize, String)>;
+
+fn parse_alien(section: &mut Section, assets: &Assets) -> SectionResult<AlienDef> {
+    let kind = parse_kind(section)?;
+    let sprite = parse_sprite_name(section, kind.default_sprite(), assets)?;
+    let pos = required(section, "pos", parse_pair)?;
+    let pos = Point::new(pos.0, pos.1);
+    let x_range = required(section, "x_range", parse_range)?;
+    let y_range = optional(section, "y_range", parse_range)?.unwrap_or((pos.y, pos.y));
+    let hp = optional(section, "hp", parse_number)?.unwrap_or_else(|| kind.default_hp());
+    let points = parse_points(section, kind)?;
+    let ai = parse_ai(section, kind)?;
+
+    Ok(AlienDef {
+        kind,
+        sprite,
+        pos,
+        x_range,
+        y_range,
+        hp,
+        points,
+        ai,
+    })
+}
+
+/// A formation is a grid of identical aliens, each sweeping `sweep` cells to
+/// the right of its own slot.
+fn parse_formation(section: &mut Section, assets: &Assets) -> SectionResult<Vec<AlienDef>> {
+    let kind = parse_kind(section)?;
+    let sprite = parse_sprite_name(section, kind.default_sprite(), assets)?;
+    let origin = required(section, "origin", parse_pair)?;
+    let columns: i16 = required(section, "columns", parse_number)?;
+    let rows: i16 = required(section, "rows", parse_number)?;
+    let spacing = required(section, "spacing", parse_pair)?;
+    let sweep: i16 = optional(section, "sweep", parse_number)?.unwrap_or(0);
+    let hp = optional(section, "hp", parse_number)?.unwrap_or_else(|| kind.default_hp());
+    let points = parse_points(section, kind)?;
+    let ai = parse_ai(section, kind)?;
+
+    if columns <= 0 || rows <= 0 {
+        return Err((
+            section.line,
+            "formation needs at least one row and column".to_string(),
+        ));
+    }
+    if sweep < 0 {
+        return Err((section.line, "sweep must not be negative".to_string()));
+    }
+
+    let mut aliens = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let pos = Point::new(origin.0 + column * spacing.0, origin.1 + row * spacing.1);
+            aliens.push(AlienDef {
+                kind,
+                sprite: sprite.clone(),
+                pos,
+                x_range: (pos.x, pos.x + sweep),
+                y_range: (pos.y, pos.y),
+                hp,
+                points,
+                ai,
+            });
+        }
+    }
+    Ok(aliens)
+}
+
+/// A row of `count` bunkers starting at `pos`, `spacing` apart.
+fn parse_bunkers(section: &mut Section, assets: &Assets) -> SectionResult<Vec<BunkerDef>> {
+    let sprite = parse_sprite_name(section, "bunker", assets)?;
+    let pos = required(section, "pos", parse_pair)?;
+    let count: i16 = optional(section, "count", parse_positive)?.unwrap_or(1);
+    let spacing = if count > 1 {
+        required(section, "spacing", parse_number)?
+    } else {
+        optional(section, "spacing", parse_number)?.unwrap_or(0)
+    };
+
+    Ok((0..count)
+        .map(|i| BunkerDef {
+            sprite: sprite.clone(),
+            pos: Point::new(pos.0 + i * spacing, pos.1),
+        })
+        .collect())
+}
+
+fn parse_march(section: &mut Section) -> SectionResult<MarchDef> {
+    let speed = optional(section, "speed", parse_positive)?.unwrap_or(30);
+    let min_speed = optional(section, "min_speed", parse_positive)?.unwrap_or(2);
+    let step_down = optional(section, "step_down", parse_positive)?.unwrap_or(1);
+    if min_speed > speed {
+        return Err((section.line, "min_speed must not exceed speed".to_string()));
+    }
+    Ok(MarchDef {
+        speed,
+        min_speed,
+        step_down,
+    })
+}
+
+fn parse_kind(section: &mut Section) -> SectionResult<AlienKind> {
+    required(section, "kind", |v| {
+        AlienKind::parse(v).ok_or_else(|| {
+            format!(
+                "unknown alien kind '{}', expected fighter, tank, assassin or boss",
+                v
+            )
+        })
+    })
+}
+
+fn parse_sprite_name(
+    section: &mut Section,
+    default: &str,
+    assets: &Assets,
+) -> SectionResult<String> {
+    let sprite = optional(section, "sprite", |v| {
+        if assets.contains(v) {
+            Ok(v.to_string())
+        } else {
+            Err(format!("no sprite asset named '{}'", v))
+        }
+    })?;
+    Ok(sprite.unwrap_or_else(|| default.to_string()))
+}
+
+fn parse_points(section: &mut Section, kind: AlienKind) -> SectionResult<u32> {
+    Ok(optional(section, "points", parse_number)?.unwrap_or_else(|| kind.default_points()))
+}
+
+fn parse_ai(section: &mut Section, kind: AlienKind) -> SectionResult<AiParams> {
+    let defaults = kind.default_ai();
+    Ok(AiParams {
+        move_speed: optional(section, "move_speed", parse_positive)?.unwrap_or(defaults.move_speed),
+        fire_rate: optional(section, "fire_rate", parse_number)?.unwrap_or(defaults.fire_rate),
+        bullet_speed: optional(section, "bullet_speed", parse_positive)?
+            .unwrap_or(defaults.bullet_speed),
+    })
+}
+
+fn required<T, F>(section: &mut Section, key: &str, parse: F) -> SectionResult<T>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    match optional(section, key, parse)? {
+        Some(value) => Ok(value),
+        None => Err((section.line, format!("missing required key '{}'", key))),
+    }
+}
+
+fn optional<T, F>(section: &mut Section, key: &str, parse: F) -> SectionResult<Option<T>>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    match section.take(key) {
+        Some((value, line)) => parse(&value)
+            .map(Some)
+            .map_err(|e| (line, format!("invalid value for '{}': {}", key, e))),
+        None => Ok(None),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse::<T>()
+        .map_err(|_| format!("'{}' is not a valid number", s))
+}
+
+fn parse_positive<T: std::str::FromStr + PartialOrd + Default>(s: &str) -> Result<T, String> {
+    let n = parse_number::<T>(s)?;
+    if n <= T::default() {
+        return Err(format!("'{}' must be greater than zero", s));
+    }
+    Ok(n)
+}
+
+fn parse_pair(s: &str) -> Result<(i16, i16), String> {
+    match s.split(',').map(str::trim).collect::<Vec<_>>().as_slice() {
+        [a, b] => Ok((parse_number(a)?, parse_number(b)?)),
+        _ => Err(format!("expected two comma separated numbers, got '{}'", s)),
+    }
+}
+
+/// A hash of everything `levels` play out with: their definitions and the
+/// shapes of the player, alien and bunker sprites. Colors are left out as
+/// they only change how a level looks. The hash is FNV-1a over a text form
+/// of the definitions, so it is the same for every build and platform and
+/// replays can check that they play back against what they recorded.
+pub fn fingerprint(levels: &[LevelDef], assets: &Assets) -> u64 {
+    let shape = |name: &str| {
+        assets
+            .cells(name)
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.symbol).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+
+    let mut items = vec![format!("player {}", shape("player"))];
+    for (idx, level) in levels.iter().enumerate() {
+        items.push(format!("level {}", idx + 1));
+        for alien in &level.aliens {
+            items.push(format!(
+                "alien {} {} {} {} {} {} {} {} {} {} {} {} {}",
+                alien.kind.name(),
+                shape(&alien.sprite),
+                alien.pos.x,
+                alien.pos.y,
+                alien.x_range.0,
+                alien.x_range.1,
+                alien.y_range.0,
+                alien.y_range.1,
+                alien.hp,
+                alien.points,
+                alien.ai.move_speed,
+                alien.ai.fire_rate.to_bits(),
+                alien.ai.bullet_speed
+            ));
+        }
+        for bunker in &level.bunkers {
+            items.push(format!(
+                "bunker {} {} {}",
+                shape(&bunker.sprite),
+                bunker.pos.x,
+                bunker.pos.y
+            ));
+        }
+        if let Some(march) = &level.march {
+            items.push(format!(
+                "march {} {} {}",
+                march.speed, march.min_speed, march.step_down
+            ));
+        }
+    }
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in items.join("\n").bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+fn parse_range(s: &str) -> Result<(i16, i16), String> {
+    let (min, max) = parse_pair(s)?;
+    if min > max {
+        return Err(format!(
+            "range start {} is greater than its end {}",
+            min, max
+        ));
+    }
+    Ok((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const LEVEL: &str = "\
+# A test level
+[alien]
+kind = boss
+pos = 10, 20
+x_range = 0, 40
+hp = 5
+
+[formation]
+kind = tank
+sprite = fighter   # tanks drawn as fighters
+origin = 2, 28
+columns = 3
+rows = 2
+spacing = 10, -5
+sweep = 4
+
+[bunker]
+pos = 10, 6
+count = 2
+spacing = 23
+
+[march]
+speed = 20
+";
+
+    fn parse(src: &str) -> Result<LevelDef, LevelFileError> {
+        parse_level("test.lvl", src, &Assets::builtin())
+    }
+
+    fn error_line(src: &str) -> (Option<usize>, String) {
+        let error = parse(src).unwrap_err();
+        assert_eq!(error.file, "test.lvl");
+        (error.line, error.message)
+    }
+
+    #[test]
+    fn parses_a_level() {
+        let level = parse(LEVEL).unwrap();
+
+        let boss = &level.aliens[0];
+        assert_eq!(boss.kind, AlienKind::Boss);
+        assert_eq!(boss.sprite, "boss");
+        assert_eq!(boss.pos, Point::new(10, 20));
+        assert_eq!(boss.x_range, (0, 40));
+        assert_eq!(boss.y_range, (20, 20));
+        assert_eq!(boss.hp, 5);
+        assert_eq!(boss.points, 500);
+        assert_eq!(boss.ai, AiParams::new(2, 1.0, 1));
+
+        let tanks = &level.aliens[1..];
+        assert_eq!(tanks.len(), 6);
+        assert!(tanks.iter().all(|t| t.sprite == "fighter" && t.hp == 2));
+        assert_eq!(tanks[4].pos, Point::new(12, 23));
+        assert_eq!(tanks[4].x_range, (12, 16));
+
+        let bunkers = level.bunkers.iter().map(|b| b.pos).collect::<Vec<_>>();
+        assert_eq!(bunkers, vec![Point::new(10, 6), Point::new(33, 6)]);
+        assert_eq!(
+            level.march,
+            Some(MarchDef {
+                speed: 20,
+                min_speed: 2,
+                step_down: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn builtin_levels_parse() {
+        assert_eq!(
+            builtin_levels(&Assets::builtin()).len(),
+            BUILTIN_LEVELS.len()
+        );
+    }
+
+    #[test]
+    fn fingerprint_follows_what_levels_play_like() {
+        let assets = Assets::builtin();
+        let levels = builtin_levels(&assets);
+        let builtin = fingerprint(&levels, &assets);
+        assert_eq!(fingerprint(&builtin_levels(&assets), &assets), builtin);
+
+        let mut moved = levels.clone();
+        moved[2].aliens[0].pos.x += 1;
+        let mut redrawn = levels.clone();
+        redrawn[1].aliens[0].sprite = "tank".to_string();
+
+        assert_ne!(fingerprint(&moved, &assets), builtin);
+        assert_ne!(fingerprint(&redrawn, &assets), builtin);
+        assert_ne!(fingerprint(&levels[1..], &assets), builtin);
+    }
+
+    #[test]
+    fn fingerprint_ignores_colors() {
+        let dir = env::temp_dir().join(format!("rustivanders-{}-colors", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fighter.spr"), "\\o/\n---\nRRR\n").unwrap();
+        let mut assets = Assets::builtin();
+        assets.load_dir(&dir).unwrap();
+        fs::write(dir.join("fighter.spr"), "\\o/\n---\nBBB\n").unwrap();
+        let mut recolored = Assets::builtin();
+        recolored.load_dir(&dir).unwrap();
+        fs::write(dir.join("fighter.spr"), "\\x/\n---\nBBB\n").unwrap();
+        let mut reshaped = Assets::builtin();
+        reshaped.load_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let levels = builtin_levels(&assets);
+
+        assert_eq!(
+            fingerprint(&levels, &recolored),
+            fingerprint(&levels, &assets)
+        );
+        assert_ne!(
+            fingerprint(&levels, &reshaped),
+            fingerprint(&levels, &assets)
+        );
+    }
+
+    #[test]
+    fn unknown_section() {
+        let (line, message) = error_line("[alien]\nkind = tank\n\n[wave]\n");
+
+        assert_eq!(line, Some(4));
+        assert_eq!(message, "unknown section 'wave'");
+    }
+
+    #[test]
+    fn unknown_alien_kind() {
+        let (line, message) = error_line("[alien]\npos = 1, 2\nkind = ufo\nx_range = 0, 4\n");
+
+        assert_eq!(line, Some(3));
+        assert!(message.contains("unknown alien kind 'ufo'"), "{}", message);
+    }
+
+    #[test]
+    fn missing_sprite() {
+        let (line, message) =
+            error_line("[alien]\nkind = tank\nsprite = ufo\npos = 1, 2\nx_range = 0, 4\n");
+
+        assert_eq!(line, Some(3));
+        assert!(
+            message.contains("no sprite asset named 'ufo'"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn missing_key_points_at_the_section() {
+        let (line, message) = error_line("# comment\n[alien]\nkind = tank\nx_range = 0, 4\n");
+
+        assert_eq!(line, Some(2));
+        assert_eq!(message, "missing required key 'pos'");
+    }
+
+    #[test]
+    fn bad_coordinates() {
+        let (line, message) = error_line("[alien]\nkind = tank\npos = 1\nx_range = 0, 4\n");
+        assert_eq!(line, Some(3));
+        assert!(
+            message.starts_with("invalid value for 'pos'"),
+            "{}",
+            message
+        );
+
+        let (line, _) = error_line("[alien]\nkind = tank\npos = a, 2\nx_range = 0, 4\n");
+        assert_eq!(line, Some(3));
+
+        let (line, message) = error_line("[alien]\nkind = tank\npos = 1, 2\nx_range = 9, 4\n");
+        assert_eq!(line, Some(4));
+        assert!(
+            message.starts_with("invalid value for 'x_range'"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn unknown_key() {
+        let (line, message) = error_line("[bunker]\npos = 1, 2\ncolour = red\n");
+
+        assert_eq!(line, Some(3));
+        assert_eq!(message, "unknown key 'colour'");
+    }
+
+    #[test]
+    fn level_without_aliens() {
+        let error = parse("[bunker]\npos = 1, 2\n").unwrap_err();
+
+        assert_eq!(error.line, None);
+        assert_eq!(error.to_string(), "test.lvl: level contains no aliens");
+    }
+}
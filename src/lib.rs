@@ -0,0 +1,32 @@
+//! The Rustivanders game engine.
+//!
+//! `Board` holds a running game: the player, the current `Level` with its
+//! aliens, bullets and bunkers, score and lives. Every call to
+//! `Board::update` advances the simulation by one tick, sprites report what
+//! should happen next as `UpdateCommand`s which the board then applies.
+//! Levels are described by `LevelDef`s, usually parsed from level files, and
+//! all randomness comes from a seeded `GameRng` so runs are reproducible.
+//!
+//! The terminal game in `main.rs` is one front end on top of this crate,
+//! `headless` drives a board without any terminal.
+
+pub mod ai;
+pub mod board;
+pub mod difficulty;
+pub mod headless;
+pub mod level;
+pub mod level_file;
+pub mod renderer;
+pub mod sprite;
+pub mod surface;
+pub mod terminal;
+pub mod util;
+
+pub use ai::{BossAi, BulletAi, InvanderAi, MarchAi, PlayerAi};
+pub use board::{Board, UpdateCommand};
+pub use difficulty::Difficulty;
+pub use level::Level;
+pub use level_file::LevelDef;
+pub use sprite::{Assets, Sprite, SpriteState};
+pub use surface::{FrameBuffer, Renderable, Surface};
+pub use util::{Direction, GameRng, Point};
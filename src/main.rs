@@ -1,14 +1,197 @@
-mod ai;
-mod board;
+mod bindings;
+mod cli;
+mod clock;
+mod config;
+mod crash;
+mod error;
 mod events;
 mod game;
-mod level;
-mod renderer;
-mod sprite;
-mod util;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod held_keys;
+mod highscores;
+#[cfg(not(feature = "crossterm"))]
+mod kitty;
+mod replay;
+mod state;
 
-use crate::game::Rustivanders;
+use crate::cli::Options;
+use crate::config::UserConfig;
+use crate::error::Error;
+use crate::game::{Rustivanders, TICK_RATE};
+use crate::replay::{Recorder, Replay};
+
+use rustivanders::board::{Board, DEFAULT_LIVES};
+use rustivanders::headless::{self, Autopilot, InputSource, Script};
+use rustivanders::level_file::{self, LevelDef};
+use rustivanders::sprite::Assets;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
 
 fn main() {
-    Rustivanders::new().run();
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    if options.version {
+        println!("rustivanders {}", env!("CARGO_PKG_VERSION"));
+        return;
+    }
+    if let Err(e) = run(options) {
+        // Leaving the alternate screen may still sit in stdout's buffer
+        let _ = io::stdout().flush();
+        eprintln!("{}", e);
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn run(options: Options) -> Result<(), Error> {
+    let assets = Assets::load_default()?;
+    let levels = level_file::load_default_levels(&assets)?;
+    let content = level_file::fingerprint(&levels, &assets);
+
+    if options.list_levels {
+        for (idx, level) in levels.iter().enumerate() {
+            println!("{:>3}  {}", idx + 1, level_summary(level));
+        }
+        return Ok(());
+    }
+
+    let replay = options
+        .replay
+        .as_deref()
+        .map(|path| Replay::load(path, content))
+        .transpose()?;
+    let start_level = match &replay {
+        Some(replay) => replay.start_level,
+        None => options.level.unwrap_or(1),
+    };
+    if start_level as usize > levels.len() {
+        return Err(Error::NoSuchLevel {
+            level: start_level,
+            count: levels.len(),
+        });
+    }
+
+    let difficulty = options.difficulty.unwrap_or_default();
+    let lives = options.lives.unwrap_or(DEFAULT_LIVES);
+
+    if let Some(ticks) = options.headless {
+        let mut input: Box<dyn InputSource> = match &options.script {
+            Some(path) => Box::new(Script::load(path)?),
+            None => Box::new(Autopilot),
+        };
+        let seed = options.seed.unwrap_or_else(rand::random);
+        let mut board = Board::starting_at(levels, assets, lives, seed, start_level)
+            .with_difficulty(difficulty);
+        println!("{}", headless::run(&mut board, ticks, input.as_mut()));
+        return Ok(());
+    }
+
+    // A replay plays with the bindings it recorded, the config isn't read
+    let (config, config_path) = match options.config {
+        _ if replay.is_some() => (UserConfig::default(), None),
+        Some(path) => (UserConfig::read(&path)?, Some(path)),
+        None => {
+            let path = config::default_path();
+            let config = match &path {
+                Some(path) => UserConfig::load(path)?,
+                None => UserConfig::default(),
+            };
+            (config, path)
+        }
+    };
+    let tick_rate = options.tick_rate.unwrap_or(TICK_RATE);
+    // A recording needs one known seed for the whole session
+    let seed = match (&replay, &options.record) {
+        (Some(replay), _) => Some(replay.seed),
+        (None, Some(_)) => Some(options.seed.unwrap_or_else(rand::random)),
+        (None, None) => options.seed,
+    };
+
+    let recorder = options
+        .record
+        .as_ref()
+        .map(|path| {
+            Recorder::create(
+                path,
+                seed.unwrap_or_default(),
+                content,
+                start_level,
+                lives,
+                difficulty,
+                tick_rate,
+                &config.bindings,
+            )
+            .map_err(|e| Error::Record(path.clone(), e))
+        })
+        .transpose()?;
+
+    // The game owns the terminal, a panic is reported once unwinding has
+    // dropped it and the terminal is back to normal
+    crash::install_hook();
+    let mut game = None;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut new_game = Rustivanders::new(levels, assets, seed)?
+            .with_start_level(start_level)
+            .with_lives(lives)
+            .with_difficulty(difficulty)
+            .with_tick_rate(tick_rate)
+            .with_bindings(config.bindings.clone(), config_path);
+        if let Some(recorder) = recorder {
+            new_game = new_game.with_recorder(recorder);
+        }
+        if let Some(replay) = replay {
+            new_game = new_game.with_replay(replay);
+        }
+        game.insert(new_game).run()
+    }));
+    match result {
+        Ok(result) => {
+            // Restores the terminal before the error is printed
+            drop(game);
+            result
+        }
+        Err(_) => {
+            let state = game.as_ref().map(Rustivanders::crash_state);
+            drop(game);
+            crash::report(state);
+            std::process::exit(101);
+        }
+    }
+}
+
+/// What a level holds, for `--list-levels`: "24 fighters, 4 bunkers".
+fn level_summary(level: &LevelDef) -> String {
+    let mut kinds = Vec::new();
+    for alien in &level.aliens {
+        match kinds.iter_mut().find(|(kind, _)| *kind == alien.kind) {
+            Some((_, count)) => *count += 1,
+            None => kinds.push((alien.kind, 1)),
+        }
+    }
+    let mut parts = kinds
+        .into_iter()
+        .map(|(kind, count)| plural(count, kind.name()))
+        .collect::<Vec<_>>();
+    parts.push(plural(level.bunkers.len(), "bunker"));
+    if level.march.is_some() {
+        parts.push("marching".to_string());
+    }
+    parts.join(", ")
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.ends_with('s')) {
+        (1, _) => format!("1 {}", noun),
+        (_, true) => format!("{} {}es", count, noun),
+        (_, false) => format!("{} {}s", count, noun),
+    }
 }
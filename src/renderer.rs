@@ -1,57 +1,390 @@
-use termion::{raw::IntoRawMode, screen::AlternateScreen};
+use crate::level::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::surface::{self, cell_position, Renderable, Surface};
+use crate::terminal::{self, TermBackend};
+use crate::util::Point;
+
+use std::io;
 use tui::{
-    backend::TermionBackend,
-    layout::{Constraint, Direction, Layout, Rect},
-    widgets::{
-        canvas::{Canvas, Context},
-        Block, Borders,
-    },
-    Terminal,
+    backend::Backend,
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+    Frame, Terminal,
 };
 
-pub trait Renderable {
-    fn render(&self, ctx: &mut Context);
+/// Columns taken by the playfield and its border.
+pub const MIN_WIDTH: u16 = SCREEN_WIDTH as u16 + 2;
+
+/// Rows taken by the HUD line, the playfield and its border.
+pub const MIN_HEIGHT: u16 = SCREEN_HEIGHT as u16 + 3;
+
+/// Whether a terminal of `width` x `height` cells can show the game.
+pub fn fits(width: u16, height: u16) -> bool {
+    width >= MIN_WIDTH && height >= MIN_HEIGHT
 }
 
-pub struct Renderer {
-    terminal: Terminal<TermionBackend<AlternateScreen<termion::raw::RawTerminal<std::io::Stdout>>>>,
+/// Centers the game on `screen` and splits it into the HUD line and the
+/// playfield below it, `None` when the screen is too small.
+fn layout(screen: Rect) -> Option<(Rect, Rect)> {
+    if !fits(screen.width, screen.height) {
+        return None;
+    }
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(Rect {
+            x: screen.x + (screen.width - MIN_WIDTH) / 2,
+            y: screen.y + (screen.height - MIN_HEIGHT) / 2,
+            width: MIN_WIDTH,
+            height: MIN_HEIGHT,
+        });
+    Some((areas[0], areas[1]))
 }
 
-impl Renderer {
-    pub fn new() -> Self {
-        let stdout = std::io::stdout().into_raw_mode().unwrap();
-        let stdout = AlternateScreen::from(stdout);
-        let backend = TermionBackend::new(stdout);
-        let terminal = Terminal::new(backend).unwrap();
-        Renderer { terminal }
+/// The playfield position under the zero based cell `col`, `row` of a
+/// `width` x `height` terminal. `None` on the border, the HUD or outside
+/// the playfield.
+pub fn playfield_point(width: u16, height: u16, col: u16, row: u16) -> Option<Point> {
+    let (_, area) = layout(Rect::new(0, 0, width, height))?;
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    surface::playfield_position(
+        col.checked_sub(inner.x)?,
+        row.checked_sub(inner.y)?,
+        inner.width,
+        inner.height,
+    )
+}
+
+/// Lines of text drawn centered over a playfield, e.g. a pause banner.
+pub struct Overlay<'a> {
+    pub background: &'a dyn Renderable,
+    pub lines: &'a [(String, Color)],
+}
+
+impl<'a> Renderable for Overlay<'a> {
+    fn render(&self, surface: &mut dyn Surface) {
+        self.background.render(surface);
+
+        let y = SCREEN_HEIGHT as i16 / 2;
+        for (row, (text, color)) in self.lines.iter().enumerate() {
+            let x = (SCREEN_WIDTH as i16 - text.len() as i16 + 1) / 2;
+            surface.draw_text(x, y - row as i16, text, *color);
+        }
+    }
+}
+
+/// Bordered tui widget showing a `Renderable`.
+struct Playfield<'a> {
+    renderable: &'a dyn Renderable,
+}
+
+impl<'a> Widget for Playfield<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default().borders(Borders::ALL);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        self.renderable.render(&mut BufferSurface {
+            buffer: buf,
+            area: inner,
+        });
+    }
+}
+
+/// `Surface` drawing into the `area` of a tui buffer.
+struct BufferSurface<'b> {
+    buffer: &'b mut Buffer,
+    area: Rect,
+}
+
+impl<'b> Surface for BufferSurface<'b> {
+    fn draw_glyph(&mut self, x: i16, y: i16, glyph: &str, fg: Color, bg: Color) {
+        if let Some((col, row)) = cell_position(x, y, self.area.width, self.area.height) {
+            self.buffer
+                .get_mut(self.area.x + col, self.area.y + row)
+                .set_symbol(glyph)
+                .set_fg(fg)
+                .set_bg(bg);
+        }
+    }
+
+    fn draw_text(&mut self, x: i16, y: i16, text: &str, fg: Color) {
+        if let Some((col, row)) = cell_position(x, y, self.area.width, self.area.height) {
+            self.buffer.set_stringn(
+                self.area.x + col,
+                self.area.y + row,
+                text,
+                (self.area.width - col) as usize,
+                Style::default().fg(fg),
+            );
+        }
     }
 }
 
+pub struct Renderer<B: Backend = TermBackend> {
+    terminal: Terminal<B>,
+}
+
 impl Renderer {
-    pub fn clear(&mut self) {
-        self.terminal.clear().unwrap();
+    /// Takes over the terminal until the renderer is dropped.
+    pub fn new() -> io::Result<Self> {
+        Renderer::with_backend(terminal::backend()?)
+    }
+}
+
+impl<B: Backend> Renderer<B> {
+    pub fn with_backend(backend: B) -> io::Result<Self> {
+        let terminal = Terminal::new(backend)?;
+        Ok(Renderer { terminal })
+    }
+
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.terminal.clear()
+    }
+
+    /// Shown instead of the game while the terminal can't fit it.
+    fn render_too_small(f: &mut Frame<B>) {
+        let size = f.size();
+        let lines = vec![
+            Spans::from(Span::styled(
+                "Terminal too small",
+                Style::default().fg(Color::LightRed),
+            )),
+            Spans::from(format!(
+                "{}x{}, need {}x{}",
+                size.width, size.height, MIN_WIDTH, MIN_HEIGHT
+            )),
+        ];
+        let top_padding = size.height.saturating_sub(lines.len() as u16) / 2;
+        let area = Rect {
+            y: size.y + top_padding,
+            height: size.height - top_padding,
+            ..size
+        };
+        let paragraph = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+    }
+
+    pub fn render(&mut self, renderable: &dyn Renderable, hud: &str) -> io::Result<()> {
+        self.terminal
+            .draw(|f| match layout(f.size()) {
+                Some((hud_area, render_area)) => {
+                    f.render_widget(Paragraph::new(hud), hud_area);
+                    f.render_widget(Playfield { renderable }, render_area);
+                }
+                None => Self::render_too_small(f),
+            })
+            .map(|_| ())
     }
 
-    pub fn render(&mut self, renderable: &dyn Renderable) {
+    /// Draws colored lines of text centered in the playfield, used by menus.
+    pub fn render_text(&mut self, hud: &str, lines: &[(String, Color)]) -> io::Result<()> {
         self.terminal
             .draw(|f| {
-                let render_area = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Percentage(100)].as_ref())
-                    .split(Rect {
-                        x: 0,
-                        y: 0,
-                        width: 96,
-                        height: 32,
-                    })[0];
-
-                let canvas = Canvas::default()
+                let (hud_area, render_area) = match layout(f.size()) {
+                    Some(areas) => areas,
+                    None => return Self::render_too_small(f),
+                };
+
+                f.render_widget(Paragraph::new(hud), hud_area);
+
+                let top_padding = (render_area.height as usize).saturating_sub(lines.len()) / 2;
+                let text = std::iter::repeat_n(Spans::default(), top_padding)
+                    .chain(lines.iter().map(|(line, color)| {
+                        Spans::from(Span::styled(line.as_str(), Style::default().fg(*color)))
+                    }))
+                    .collect::<Vec<_>>();
+                let paragraph = Paragraph::new(text)
                     .block(Block::default().borders(Borders::ALL))
-                    .paint(|ctx| renderable.render(ctx))
-                    .x_bounds([0.0, 94.0])
-                    .y_bounds([0.0, 30.0]);
-                f.render_widget(canvas, render_area);
+                    .alignment(Alignment::Center);
+                f.render_widget(paragraph, render_area);
+            })
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board, DEFAULT_LIVES};
+    use crate::level_file;
+    use crate::sprite::Assets;
+    use crate::surface::FrameBuffer;
+
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use tui::backend::TestBackend;
+
+    fn renderer() -> Renderer<TestBackend> {
+        Renderer::with_backend(TestBackend::new(96, 33)).unwrap()
+    }
+
+    fn board() -> Board {
+        let assets = Assets::builtin();
+        let levels = level_file::builtin_levels(&assets);
+        Board::new(levels, assets, DEFAULT_LIVES, 42)
+    }
+
+    /// Symbols of the last drawn frame, one line per row.
+    fn frame(renderer: &Renderer<TestBackend>) -> String {
+        let buffer = renderer.terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            let row = (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect::<String>();
+            text.push_str(row.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Compares `actual` with `src/snapshots/<name>.txt`. Run the tests with
+    /// `UPDATE_SNAPSHOTS=1` to write the current frames instead.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("{}.txt", name));
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("missing snapshot {}: {}", path.display(), e));
+        assert!(
+            actual == expected,
+            "frame differs from {}\n\nactual:\n{}",
+            path.display(),
+            actual
+        );
+    }
+
+    #[test]
+    fn first_level() {
+        let board = board();
+        let mut renderer = renderer();
+
+        renderer.render(&board, &board.hud()).unwrap();
+
+        assert_snapshot("first_level", &frame(&renderer));
+    }
+
+    #[test]
+    fn boss_level() {
+        let mut board = board();
+        while board.level.number < 4 {
+            board.level.aliens.clear();
+            board.next_level();
+        }
+        let mut renderer = renderer();
+
+        renderer.render(&board, &board.hud()).unwrap();
+
+        assert_snapshot("boss_level", &frame(&renderer));
+    }
+
+    #[test]
+    fn paused_overlay() {
+        let board = board();
+        let lines = [
+            ("PAUSED".to_string(), Color::Yellow),
+            ("Press 'p' to resume".to_string(), Color::LightYellow),
+        ];
+        let overlay = Overlay {
+            background: &board,
+            lines: &lines,
+        };
+        let mut renderer = renderer();
+
+        renderer.render(&overlay, &board.hud()).unwrap();
+
+        assert_snapshot("paused_overlay", &frame(&renderer));
+    }
+
+    #[test]
+    fn framebuffer_matches_the_terminal_playfield() {
+        let board = board();
+        let mut renderer = renderer();
+
+        renderer.render(&board, &board.hud()).unwrap();
+
+        // Strip the HUD line and the border around the playfield
+        let terminal = frame(&renderer)
+            .lines()
+            .skip(2)
+            .take(SCREEN_HEIGHT)
+            .map(|line| {
+                line.trim_start_matches('│')
+                    .trim_end_matches('│')
+                    .trim_end()
             })
-            .unwrap();
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        assert_eq!(FrameBuffer::render(&board).to_string(), terminal);
+    }
+
+    #[test]
+    fn playfield_is_centered_on_large_terminals() {
+        let board = board();
+        let mut renderer =
+            Renderer::with_backend(TestBackend::new(MIN_WIDTH + 24, MIN_HEIGHT + 7)).unwrap();
+
+        renderer.render(&board, &board.hud()).unwrap();
+
+        let buffer = renderer.terminal.backend().buffer();
+        assert_eq!(buffer.get(12, 3).symbol, "S");
+        assert_eq!(buffer.get(12, 4).symbol, "┌");
+        assert_eq!(
+            buffer.get(12 + MIN_WIDTH - 1, 3 + MIN_HEIGHT - 1).symbol,
+            "┘"
+        );
+        assert_eq!(buffer.get(11, 4).symbol, " ");
+    }
+
+    #[test]
+    fn terminal_cells_map_onto_the_playfield() {
+        let (width, height) = (MIN_WIDTH + 24, MIN_HEIGHT + 7);
+
+        // The playfield border starts at column 12, row 4
+        assert_eq!(
+            playfield_point(width, height, 13, 5),
+            Some(Point::new(0, SCREEN_HEIGHT as i16))
+        );
+        assert_eq!(
+            playfield_point(width, height, 12 + MIN_WIDTH - 2, 4 + SCREEN_HEIGHT as u16),
+            Some(Point::new(SCREEN_WIDTH as i16, 0))
+        );
+        assert_eq!(playfield_point(width, height, 12, 5), None);
+        assert_eq!(playfield_point(width, height, 13, 4), None);
+        assert_eq!(playfield_point(width, height, 12 + MIN_WIDTH - 1, 5), None);
+        assert_eq!(playfield_point(40, 8, 5, 5), None);
+    }
+
+    #[test]
+    fn too_small_terminal() {
+        let board = board();
+        let mut renderer = Renderer::with_backend(TestBackend::new(40, 8)).unwrap();
+
+        renderer.render(&board, &board.hud()).unwrap();
+
+        assert_snapshot("too_small", &frame(&renderer));
+    }
+
+    #[test]
+    fn text_screen() {
+        let lines = vec![
+            ("HIGH SCORES".to_string(), Color::LightGreen),
+            (String::new(), Color::Reset),
+            ("No scores yet".to_string(), Color::Gray),
+        ];
+        let mut renderer = renderer();
+
+        renderer.render_text("Score: 000000", &lines).unwrap();
+
+        assert_snapshot("text_screen", &frame(&renderer));
     }
 }
@@ -0,0 +1,589 @@
+use crate::bindings::{Action, Bindings};
+use crate::events::Event;
+use crate::game::TICK_RATE;
+
+use rustivanders::board::DEFAULT_LIVES;
+use rustivanders::difficulty::Difficulty;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use termion::event::{Key, MouseButton, MouseEvent};
+
+const HEADER: &str = "# rustivanders replay";
+
+pub type InputEvent = Event<Key, MouseEvent>;
+
+/// Writes every input of a session to a replay file as it happens.
+///
+/// The file starts with the seed, a hash of the levels and sprites played,
+/// the start level, the lives per game, the difficulty, the tick rate in
+/// milliseconds, the key bindings and the scores of the high score
+/// table, which decides whether a game over asks for initials. One
+/// `<tick> key|release|pad|mouse ...` line per event where `tick` is the number
+/// of simulation steps run before the event was handled. Mouse positions
+/// are playfield positions rather than terminal cells.
+pub struct Recorder {
+    file: File,
+    path: PathBuf,
+}
+
+impl Recorder {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        path: &Path,
+        seed: u64,
+        content: u64,
+        start_level: u16,
+        lives: u16,
+        difficulty: Difficulty,
+        tick_rate: Duration,
+        bindings: &Bindings,
+    ) -> io::Result<Recorder> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", HEADER)?;
+        writeln!(file, "seed {}", seed)?;
+        writeln!(file, "content {:016x}", content)?;
+        writeln!(file, "level {}", start_level)?;
+        writeln!(file, "lives {}", lives)?;
+        writeln!(file, "difficulty {}", difficulty.name())?;
+        writeln!(file, "tick_rate {}", tick_rate.as_millis())?;
+        for (key, action) in bindings.iter() {
+            if let Some(key) = encode_key(&key) {
+                writeln!(file, "bind {} {}", action.name(), key)?;
+            }
+        }
+        Ok(Recorder {
+            file,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records the scores of the local high score table, must come before
+    /// the first event.
+    pub fn record_high_scores(&mut self, scores: &[u32]) -> io::Result<()> {
+        write!(self.file, "highscores")?;
+        for score in scores {
+            write!(self.file, " {}", score)?;
+        }
+        writeln!(self.file)
+    }
+
+    /// Appends `event`. Lines are written unbuffered so a session that ends
+    /// abruptly still leaves a usable replay behind.
+    pub fn record(&mut self, tick: u64, event: &InputEvent) -> io::Result<()> {
+        match encode(event) {
+            Some(encoded) => writeln!(self.file, "{} {}", tick, encoded),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Inputs read back from a replay file, in the order they were recorded.
+pub struct Replay {
+    pub seed: u64,
+    /// `level_file::fingerprint` of the levels and sprites the session was
+    /// played with, `None` for recordings made before it was recorded.
+    pub content: Option<u64>,
+    pub start_level: u16,
+    pub lives: u16,
+    /// Difficulty the session started at, later changes are in the inputs.
+    pub difficulty: Difficulty,
+    /// Tick rate the session was played at, replays run at the same speed.
+    pub tick_rate: Duration,
+    /// Scores of the high score table the session was recorded against,
+    /// `None` for recordings made before they were recorded.
+    pub high_scores: Option<Vec<u32>>,
+    /// Bindings the session was recorded with, `None` for recordings
+    /// made before bindings were configurable.
+    pub bindings: Option<Bindings>,
+    events: VecDeque<(u64, InputEvent)>,
+}
+
+impl Replay {
+    /// Reads the replay at `path`, which must have been recorded with the
+    /// levels and sprites whose fingerprint is `content`. Played with any
+    /// others it would not play out the same.
+    pub fn load(path: &Path, content: u64) -> Result<Replay, ReplayError> {
+        let file = path.display().to_string();
+        let src =
+            fs::read_to_string(path).map_err(|e| ReplayError::new(&file, None, e.to_string()))?;
+        let replay = Replay::parse(&file, &src)?;
+        let message = match replay.content {
+            Some(recorded) if recorded == content => return Ok(replay),
+            Some(_) => "recorded with other levels or sprites than the ones loaded",
+            None => "recorded by an older version that played differently",
+        };
+        Err(ReplayError::new(&file, None, message.to_string()))
+    }
+
+    pub fn parse(file: &str, src: &str) -> Result<Replay, ReplayError> {
+        let mut seed = None;
+        let mut content = None;
+        // Recordings made before these were recorded used the defaults
+        let mut start_level = 1;
+        let mut lives = DEFAULT_LIVES;
+        let mut difficulty = Difficulty::Normal;
+        let mut tick_rate = TICK_RATE;
+        let mut bindings = None;
+        let mut high_scores = None;
+        let mut events = VecDeque::new();
+        let mut last_tick = 0;
+
+        for (idx, raw) in src.lines().enumerate() {
+            let line_no = idx + 1;
+            let error = |message: String| ReplayError::new(file, Some(line_no), message);
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if let ["seed", value] = tokens.as_slice() {
+                if seed.is_some() {
+                    return Err(error("duplicate seed".to_string()));
+                }
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| error(format!("invalid seed '{}'", value)))?,
+                );
+                continue;
+            }
+            if seed.is_none() {
+                return Err(error("events must follow the seed".to_string()));
+            }
+            match tokens.as_slice() {
+                ["level", value] => {
+                    start_level = value
+                        .parse()
+                        .ok()
+                        .filter(|level| *level > 0)
+                        .ok_or_else(|| error(format!("invalid level '{}'", value)))?;
+                    continue;
+                }
+                ["content", value] => {
+                    content = Some(
+                        u64::from_str_radix(value, 16)
+                            .map_err(|_| error(format!("invalid content hash '{}'", value)))?,
+                    );
+                    continue;
+                }
+                ["lives", value] => {
+                    lives = value
+                        .parse()
+                        .ok()
+                        .filter(|lives| *lives > 0)
+                        .ok_or_else(|| error(format!("invalid lives '{}'", value)))?;
+                    continue;
+                }
+                ["difficulty", value] => {
+                    difficulty = Difficulty::from_name(value)
+                        .ok_or_else(|| error(format!("unknown difficulty '{}'", value)))?;
+                    continue;
+                }
+                ["highscores", scores @ ..] => {
+                    let scores = scores
+                        .iter()
+                        .map(|score| score.parse())
+                        .collect::<Result<Vec<u32>, _>>()
+                        .map_err(|_| error(format!("invalid high scores '{}'", line)))?;
+                    high_scores = Some(scores);
+                    continue;
+                }
+                ["tick_rate", value] => {
+                    tick_rate = value
+                        .parse()
+                        .ok()
+                        .filter(|ms| *ms > 0)
+                        .map(Duration::from_millis)
+                        .ok_or_else(|| error(format!("invalid tick rate '{}'", value)))?;
+                    continue;
+                }
+                _ => {}
+            }
+            if let ["bind", action, key @ ..] = tokens.as_slice() {
+                let action = Action::from_name(action)
+                    .ok_or_else(|| error(format!("unknown action '{}'", action)))?;
+                let key =
+                    decode_key(key).ok_or_else(|| error(format!("invalid key '{}'", line)))?;
+                bindings
+                    .get_or_insert_with(Bindings::empty)
+                    .bind(action, key);
+                continue;
+            }
+
+            let tick: u64 = tokens[0]
+                .parse()
+                .map_err(|_| error(format!("invalid tick '{}'", tokens[0])))?;
+            if tick < last_tick {
+                return Err(error(format!("tick {} is before tick {}", tick, last_tick)));
+            }
+            let event =
+                decode(&tokens[1..]).ok_or_else(|| error(format!("invalid event '{}'", line)))?;
+            last_tick = tick;
+            events.push_back((tick, event));
+        }
+
+        let seed = seed.ok_or_else(|| ReplayError::new(file, None, "missing seed".to_string()))?;
+        Ok(Replay {
+            seed,
+            content,
+            start_level,
+            lives,
+            difficulty,
+            tick_rate,
+            high_scores,
+            bindings,
+            events,
+        })
+    }
+
+    /// Takes the next event that was handled at or before `tick`.
+    pub fn next_due(&mut self, tick: u64) -> Option<InputEvent> {
+        match self.events.front() {
+            Some((due, _)) if *due <= tick => self.events.pop_front().map(|(_, event)| event),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ReplayError {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ReplayError {
+    fn new(file: &str, line: Option<usize>, message: String) -> Self {
+        ReplayError {
+            file: file.to_string(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+fn encode(event: &InputEvent) -> Option<String> {
+    let encoded = match event {
+        Event::Input(key) => format!("key {}", encode_key(key)?),
+        Event::Release(key) => format!("release {}", encode_key(key)?),
+        Event::Pad { action, pressed } => format!(
+            "pad {} {}",
+            action.name(),
+            if *pressed { "press" } else { "release" }
+        ),
+        Event::MouseInput(MouseEvent::Press(button, x, y)) => {
+            format!("mouse press {} {} {}", encode_button(button), x, y)
+        }
+        Event::MouseInput(MouseEvent::Release(x, y)) => format!("mouse release {} {}", x, y),
+        Event::MouseInput(MouseEvent::Hold(x, y)) => format!("mouse hold {} {}", x, y),
+        // Resizing only changes how the game is drawn
+        Event::Resize(_, _) => return None,
+    };
+    Some(encoded)
+}
+
+fn decode(tokens: &[&str]) -> Option<InputEvent> {
+    let event = match tokens {
+        ["key", key @ ..] => Event::Input(decode_key(key)?),
+        ["release", key @ ..] => Event::Release(decode_key(key)?),
+        ["pad", action, state] => Event::Pad {
+            action: Action::from_name(action)?,
+            pressed: match *state {
+                "press" => true,
+                "release" => false,
+                _ => return None,
+            },
+        },
+        ["mouse", "press", button, x, y] => Event::MouseInput(MouseEvent::Press(
+            decode_button(button)?,
+            x.parse().ok()?,
+            y.parse().ok()?,
+        )),
+        ["mouse", "release", x, y] => {
+            Event::MouseInput(MouseEvent::Release(x.parse().ok()?, y.parse().ok()?))
+        }
+        ["mouse", "hold", x, y] => {
+            Event::MouseInput(MouseEvent::Hold(x.parse().ok()?, y.parse().ok()?))
+        }
+        _ => return None,
+    };
+    Some(event)
+}
+
+fn encode_key(key: &Key) -> Option<String> {
+    let encoded = match key {
+        Key::Backspace => "backspace".to_string(),
+        Key::Left => "left".to_string(),
+        Key::Right => "right".to_string(),
+        Key::Up => "up".to_string(),
+        Key::Down => "down".to_string(),
+        Key::Home => "home".to_string(),
+        Key::End => "end".to_string(),
+        Key::PageUp => "pageup".to_string(),
+        Key::PageDown => "pagedown".to_string(),
+        Key::BackTab => "backtab".to_string(),
+        Key::Delete => "delete".to_string(),
+        Key::Insert => "insert".to_string(),
+        Key::F(n) => format!("f {}", n),
+        Key::Char(c) => format!("char {}", encode_char(*c)),
+        Key::Alt(c) => format!("alt {}", encode_char(*c)),
+        Key::Ctrl(c) => format!("ctrl {}", encode_char(*c)),
+        Key::Null => "null".to_string(),
+        Key::Esc => "esc".to_string(),
+        _ => return None,
+    };
+    Some(encoded)
+}
+
+fn decode_key(tokens: &[&str]) -> Option<Key> {
+    let key = match tokens {
+        ["backspace"] => Key::Backspace,
+        ["left"] => Key::Left,
+        ["right"] => Key::Right,
+        ["up"] => Key::Up,
+        ["down"] => Key::Down,
+        ["home"] => Key::Home,
+        ["end"] => Key::End,
+        ["pageup"] => Key::PageUp,
+        ["pagedown"] => Key::PageDown,
+        ["backtab"] => Key::BackTab,
+        ["delete"] => Key::Delete,
+        ["insert"] => Key::Insert,
+        ["f", n] => Key::F(n.parse().ok()?),
+        ["char", c] => Key::Char(decode_char(c)?),
+        ["alt", c] => Key::Alt(decode_char(c)?),
+        ["ctrl", c] => Key::Ctrl(decode_char(c)?),
+        ["null"] => Key::Null,
+        ["esc"] => Key::Esc,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Visible characters are written as is, whitespace and control characters
+/// as `U+XXXX` so every line splits cleanly on whitespace.
+fn encode_char(c: char) -> String {
+    if c.is_whitespace() || c.is_control() {
+        format!("U+{:04X}", c as u32)
+    } else {
+        c.to_string()
+    }
+}
+
+fn decode_char(token: &str) -> Option<char> {
+    match token.strip_prefix("U+") {
+        Some(hex) => char::from_u32(u32::from_str_radix(hex, 16).ok()?),
+        None => {
+            let mut chars = token.chars();
+            let c = chars.next()?;
+            chars.next().is_none().then_some(c)
+        }
+    }
+}
+
+fn encode_button(button: &MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+        MouseButton::WheelUp => "wheelup",
+        MouseButton::WheelDown => "wheeldown",
+    }
+}
+
+fn decode_button(token: &str) -> Option<MouseButton> {
+    match token {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        "wheelup" => Some(MouseButton::WheelUp),
+        "wheeldown" => Some(MouseButton::WheelDown),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highscores::{HighScore, HighScores};
+
+    use std::env;
+
+    const CONTENT: u64 = 0x0123_4567_89ab_cdef;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("rustivanders-{}-{}", std::process::id(), name))
+    }
+
+    /// Records `events` one tick apart and reads the file back.
+    fn round_trip(name: &str, scores: &[u32], events: &[InputEvent]) -> Replay {
+        let path = temp_path(name);
+        let mut recorder = Recorder::create(
+            &path,
+            42,
+            CONTENT,
+            2,
+            5,
+            Difficulty::Hard,
+            Duration::from_millis(20),
+            &Bindings::preset("wasd").unwrap(),
+        )
+        .unwrap();
+        recorder.record_high_scores(scores).unwrap();
+        for (tick, event) in events.iter().enumerate() {
+            recorder.record(tick as u64, event).unwrap();
+        }
+        let replay = Replay::load(&path, CONTENT);
+        fs::remove_file(&path).unwrap();
+        replay.unwrap()
+    }
+
+    /// Loads `src` from a file as a replay of `CONTENT`.
+    fn load(name: &str, src: &str) -> Result<Replay, ReplayError> {
+        let path = temp_path(name);
+        fs::write(&path, src).unwrap();
+        let replay = Replay::load(&path, CONTENT);
+        fs::remove_file(&path).unwrap();
+        replay
+    }
+
+    fn all_events(replay: &mut Replay) -> Vec<InputEvent> {
+        std::iter::from_fn(|| replay.next_due(u64::MAX)).collect()
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let events = vec![
+            Event::Input(Key::Char('x')),
+            Event::Input(Key::Char(' ')),
+            Event::Input(Key::Char('\n')),
+            Event::Input(Key::Ctrl('c')),
+            Event::Input(Key::F(5)),
+            Event::Release(Key::Left),
+            Event::Pad {
+                action: Action::Fire,
+                pressed: true,
+            },
+            Event::Pad {
+                action: Action::MoveLeft,
+                pressed: false,
+            },
+            Event::MouseInput(MouseEvent::Press(MouseButton::Right, 10, 4)),
+            Event::MouseInput(MouseEvent::Hold(11, 4)),
+            Event::MouseInput(MouseEvent::Release(12, 5)),
+        ];
+
+        let mut replay = round_trip("events", &[], &events);
+
+        assert_eq!(replay.seed, 42);
+        assert_eq!(replay.content, Some(CONTENT));
+        assert_eq!(replay.start_level, 2);
+        assert_eq!(replay.lives, 5);
+        assert_eq!(replay.difficulty, Difficulty::Hard);
+        assert_eq!(replay.tick_rate, Duration::from_millis(20));
+        assert_eq!(replay.bindings, Some(Bindings::preset("wasd").unwrap()));
+        assert_eq!(all_events(&mut replay), events);
+    }
+
+    #[test]
+    fn replays_of_other_content_are_rejected() {
+        let other = load("other", "seed 1\ncontent 0123456789abcdee\n").err();
+        let older = load("older", "seed 1\n").err();
+
+        assert!(other.unwrap().message.contains("other levels or sprites"));
+        assert!(older.unwrap().message.contains("older version"));
+        assert!(load("same", "seed 1\ncontent 0123456789abcdef\n").is_ok());
+    }
+
+    #[test]
+    fn resizes_are_not_recorded() {
+        let events = vec![Event::Resize(80, 24), Event::Input(Key::Esc)];
+
+        let mut replay = round_trip("resize", &[], &events);
+
+        assert_eq!(all_events(&mut replay), vec![Event::Input(Key::Esc)]);
+    }
+
+    #[test]
+    fn high_scores_qualify_like_the_recorded_table() {
+        let mut table = HighScores::default();
+        for score in (1..=10).map(|n| n * 100) {
+            table.insert(HighScore::new("ABC", score, 1));
+        }
+        let scores = table.entries.iter().map(|e| e.score).collect::<Vec<_>>();
+
+        let replay = round_trip("highscores", &scores, &[]);
+        let replayed = HighScores::from_scores(replay.high_scores.as_deref().unwrap());
+
+        for score in [50, 100, 150, 2000] {
+            assert_eq!(
+                replayed.qualifies(score),
+                table.qualifies(score),
+                "{}",
+                score
+            );
+        }
+        assert!(!replayed.qualifies(50));
+    }
+
+    #[test]
+    fn chars_are_written_as_code_points_where_needed() {
+        let src = "seed 1\n0 key char U+0020\n1 key char U+00E9\n2 key char q\n";
+
+        let mut replay = Replay::parse("test", src).unwrap();
+
+        assert_eq!(
+            all_events(&mut replay),
+            vec![
+                Event::Input(Key::Char(' ')),
+                Event::Input(Key::Char('é')),
+                Event::Input(Key::Char('q')),
+            ]
+        );
+        assert_eq!(replay.high_scores, None);
+        assert_eq!(replay.lives, DEFAULT_LIVES);
+    }
+
+    #[test]
+    fn malformed_lines_report_their_line_number() {
+        let cases = [
+            ("0 key char q\n", 1, "events must follow the seed"),
+            ("seed 1\n\n# comment\n5 key teleport\n", 4, "invalid event"),
+            (
+                "seed 1\n5 key left\n3 key right\n",
+                3,
+                "tick 3 is before tick 5",
+            ),
+            ("seed 1\nhighscores 10 x\n", 2, "invalid high scores"),
+            ("seed 1\nlives 0\n", 2, "invalid lives"),
+            ("seed 1\ncontent xyz\n", 2, "invalid content hash"),
+        ];
+        for (src, line, message) in cases {
+            let error = Replay::parse("test", src).err().unwrap();
+
+            assert_eq!(error.line, Some(line), "{}", src);
+            assert!(error.message.contains(message), "{}", error);
+        }
+    }
+}
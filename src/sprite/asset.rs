@@ -0,0 +1,336 @@
+use crate::sprite::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use tui::style::Color;
+
+/// Directory scanned for `*.spr` files when the game starts.
+pub const ASSETS_DIR: &str = "assets";
+
+const ASSET_FILE_EXTENSION: &str = "spr";
+
+const COLOR_SEPARATOR: &str = "---";
+
+const BUILTIN_ASSETS: [(&str, &str); 6] = [
+    ("player", include_str!("../../assets/player.spr")),
+    ("fighter", include_str!("../../assets/fighter.spr")),
+    ("tank", include_str!("../../assets/tank.spr")),
+    ("assassin", include_str!("../../assets/assassin.spr")),
+    ("boss", include_str!("../../assets/boss.spr")),
+    ("bunker", include_str!("../../assets/bunker.spr")),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetError {
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl AssetError {
+    fn new(file: &str, line: Option<usize>, message: String) -> Self {
+        AssetError {
+            file: file.to_string(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+/// Sprite art keyed by asset name, which is the file name without extension.
+#[derive(Clone, Default)]
+pub struct Assets {
+    sprites: HashMap<String, Vec<Vec<Cell>>>,
+}
+
+impl Assets {
+    /// Assets shipped inside the binary.
+    pub fn builtin() -> Self {
+        let mut assets = Assets::default();
+        for (name, src) in BUILTIN_ASSETS.iter() {
+            let cells =
+                parse_sprite(name, src).unwrap_or_else(|e| panic!("invalid builtin asset {}", e));
+            assets.sprites.insert(name.to_string(), cells);
+        }
+        assets
+    }
+
+    /// Builtin assets, extended or overridden by the files in `ASSETS_DIR` if it exists.
+    pub fn load_default() -> Result<Self, AssetError> {
+        let mut assets = Assets::builtin();
+        let dir = Path::new(ASSETS_DIR);
+        if dir.is_dir() {
+            assets.load_dir(dir)?;
+        }
+        Ok(assets)
+    }
+
+    /// Loads every `*.spr` file from `dir`, replacing assets with the same name.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<(), AssetError> {
+        let dir_name = dir.display().to_string();
+        let entries =
+            fs::read_dir(dir).map_err(|e| AssetError::new(&dir_name, None, e.to_string()))?;
+
+        for entry in entries {
+            let path = entry
+                .map_err(|e| AssetError::new(&dir_name, None, e.to_string()))?
+                .path();
+            let name = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) if path.extension().is_some_and(|e| e == ASSET_FILE_EXTENSION) => {
+                    name.to_string()
+                }
+                _ => continue,
+            };
+            let file = path.display().to_string();
+            let src = fs::read_to_string(&path)
+                .map_err(|e| AssetError::new(&file, None, e.to_string()))?;
+            self.sprites.insert(name, parse_sprite(&file, &src)?);
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.sprites.contains_key(name)
+    }
+
+    /// Returns a copy of the cells of the named sprite.
+    ///
+    /// Panics when the asset is missing, level files are validated against
+    /// the loaded assets so this only happens on programming errors.
+    pub fn cells(&self, name: &str) -> Vec<Vec<Cell>> {
+        match self.sprites.get(name) {
+            Some(cells) => cells.clone(),
+            None => panic!("missing sprite asset '{}'", name),
+        }
+    }
+}
+
+/// Parses sprite art. `file` is only used to label errors.
+///
+/// The file holds a glyph grid, a `---` separator line and a color grid of the
+/// same shape where each letter gives the color of the glyph above it. Spaces
+/// are transparent, rows shorter than the widest one are padded with spaces.
+pub fn parse_sprite(file: &str, src: &str) -> Result<Vec<Vec<Cell>>, AssetError> {
+    let lines = src.lines().map(str::trim_end).collect::<Vec<_>>();
+    let separator = match lines.iter().position(|l| *l == COLOR_SEPARATOR) {
+        Some(idx) => idx,
+        None => {
+            return Err(AssetError::new(
+                file,
+                None,
+                format!(
+                    "missing '{}' line between glyphs and colors",
+                    COLOR_SEPARATOR
+                ),
+            ))
+        }
+    };
+
+    let glyphs = &lines[..separator];
+    let mut colors = &lines[separator + 1..];
+    while let Some((last, rest)) = colors.split_last() {
+        if !last.is_empty() {
+            break;
+        }
+        colors = rest;
+    }
+
+    if glyphs.is_empty() {
+        return Err(AssetError::new(
+            file,
+            Some(1),
+            "sprite has no glyphs".to_string(),
+        ));
+    }
+    if colors.len() != glyphs.len() {
+        return Err(AssetError::new(
+            file,
+            Some(separator + 1),
+            format!(
+                "{} glyph rows but {} color rows",
+                glyphs.len(),
+                colors.len()
+            ),
+        ));
+    }
+
+    let width = glyphs.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut cells = Vec::new();
+    for (row, (glyph_row, color_row)) in glyphs.iter().zip(colors.iter()).enumerate() {
+        let line = separator + row + 2;
+        let glyph_row = glyph_row.chars().collect::<Vec<_>>();
+        let color_row = color_row.chars().collect::<Vec<_>>();
+        if color_row.len() > glyph_row.len() {
+            return Err(AssetError::new(
+                file,
+                Some(line),
+                "color row is longer than its glyph row".to_string(),
+            ));
+        }
+
+        let mut cell_row = Vec::new();
+        for column in 0..width {
+            let glyph = glyph_row.get(column).copied().unwrap_or(' ');
+            let color = color_row.get(column).copied().unwrap_or(' ');
+            if glyph == ' ' {
+                cell_row.push(Cell::new(' ', Color::Reset));
+                continue;
+            }
+            match parse_color(color) {
+                Some(color) => cell_row.push(Cell::new(glyph, color)),
+                None => {
+                    return Err(AssetError::new(
+                        file,
+                        Some(line),
+                        format!(
+                            "invalid color '{}' for glyph '{}' in column {}",
+                            color,
+                            glyph,
+                            column + 1
+                        ),
+                    ))
+                }
+            }
+        }
+        cells.push(cell_row);
+    }
+    Ok(cells)
+}
+
+fn parse_color(c: char) -> Option<Color> {
+    match c {
+        'K' => Some(Color::Black),
+        'R' => Some(Color::Red),
+        'G' => Some(Color::Green),
+        'Y' => Some(Color::Yellow),
+        'B' => Some(Color::Blue),
+        'M' => Some(Color::Magenta),
+        'C' => Some(Color::Cyan),
+        'w' => Some(Color::Gray),
+        'k' => Some(Color::DarkGray),
+        'r' => Some(Color::LightRed),
+        'g' => Some(Color::LightGreen),
+        'y' => Some(Color::LightYellow),
+        'b' => Some(Color::LightBlue),
+        'm' => Some(Color::LightMagenta),
+        'c' => Some(Color::LightCyan),
+        'W' => Some(Color::White),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(src: &str) -> Vec<Vec<(char, Color)>> {
+        parse_sprite("test.spr", src)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.into_iter().map(|c| (c.symbol, c.color)).collect())
+            .collect()
+    }
+
+    fn error(src: &str) -> AssetError {
+        let error = parse_sprite("test.spr", src).unwrap_err();
+        assert_eq!(error.file, "test.spr");
+        error
+    }
+
+    #[test]
+    fn parses_glyphs_and_colors() {
+        let sprite = cells("<^>\n V\n---\nRgR\n W\n\n");
+
+        assert_eq!(
+            sprite,
+            vec![
+                vec![
+                    ('<', Color::Red),
+                    ('^', Color::LightGreen),
+                    ('>', Color::Red)
+                ],
+                vec![
+                    (' ', Color::Reset),
+                    ('V', Color::White),
+                    (' ', Color::Reset)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn non_ascii_glyphs_are_kept() {
+        let sprite = cells("█▀█\n---\nbBb\n");
+
+        assert_eq!(
+            sprite,
+            vec![vec![
+                ('█', Color::LightBlue),
+                ('▀', Color::Blue),
+                ('█', Color::LightBlue)
+            ]]
+        );
+    }
+
+    #[test]
+    fn builtin_assets_parse() {
+        let assets = Assets::builtin();
+
+        assert!(BUILTIN_ASSETS.iter().all(|(name, _)| assets.contains(name)));
+    }
+
+    #[test]
+    fn missing_separator() {
+        let error = error("<^>\nRgR\n");
+
+        assert_eq!(error.line, None);
+        assert_eq!(
+            error.message,
+            "missing '---' line between glyphs and colors"
+        );
+    }
+
+    #[test]
+    fn mismatched_row_counts() {
+        let error = error("<^>\n V\n---\nRgR\n");
+
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.message, "2 glyph rows but 1 color rows");
+    }
+
+    #[test]
+    fn color_row_longer_than_glyphs() {
+        let error = error("<^>\n V\n---\nRgR\n WWW\n");
+
+        assert_eq!(error.line, Some(5));
+        assert_eq!(error.message, "color row is longer than its glyph row");
+    }
+
+    #[test]
+    fn unknown_color_letter() {
+        let error = error("<^>\n---\nRxR\n");
+
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.message, "invalid color 'x' for glyph '^' in column 2");
+    }
+
+    #[test]
+    fn glyph_without_a_color() {
+        let error = error("<^>\n---\nRg\n");
+
+        assert_eq!(error.line, Some(3));
+        assert!(error.message.starts_with("invalid color ' '"), "{}", error);
+    }
+}
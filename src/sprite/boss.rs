@@ -3,271 +3,26 @@ use crate::board::UpdateCommand;
 use crate::sprite::Cell;
 use crate::sprite::Sprite;
 use crate::sprite::SpriteState;
-use crate::util::{Direction, Point};
-use tui::style::Color;
+use crate::util::{Direction, GameRng, Point};
 
 pub struct Boss {
     pub state: SpriteState,
     pub ai: BossAi,
+    pub points: u32,
 }
 
 impl Boss {
-    pub fn new_boss(x: i16, y: i16, x_range: &(i16, i16), y_range: &(i16, i16)) -> Self {
+    pub fn new(x: i16, y: i16, hp: u16, points: u32, cells: Vec<Vec<Cell>>, ai: BossAi) -> Self {
         Boss {
             state: SpriteState {
                 id: 0,
-                hp: 20,
+                hp,
                 pos: Point::new(x, y),
                 direction: Direction::Down,
-                cells: vec![
-                    vec![
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("+", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("+", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                    ],
-                    vec![
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("/", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("\\", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                    ],
-                    vec![
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("/", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("/", Color::Yellow),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("\\", Color::Yellow),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("\\", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                    ],
-                    vec![
-                        Cell::new("+", Color::LightCyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("+", Color::LightGreen),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(":", Color::White),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("+", Color::LightGreen),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("=", Color::Cyan),
-                        Cell::new("+", Color::LightCyan),
-                    ],
-                    vec![
-                        Cell::new("V", Color::Red),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("", Color::Green),
-                        Cell::new("\\", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("\\", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("_", Color::Yellow),
-                        Cell::new("/", Color::Yellow),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("/", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("V", Color::Red),
-                    ],
-                    vec![
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("\\", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("/", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                    ],
-                    vec![
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new("+", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("~", Color::Green),
-                        Cell::new("+", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                        Cell::new(" ", Color::Green),
-                    ],
-                ],
+                cells,
             },
-            ai: BossAi::new(x_range, y_range, 2, 1.0),
+            ai,
+            points,
         }
     }
 }
@@ -277,15 +32,27 @@ impl<'a> Sprite<'a> for Boss {
         self.state.id = id;
     }
 
-    fn update(&mut self) -> Vec<UpdateCommand> {
-        self.ai.update(&mut self.state)
+    fn update(&mut self, rng: &mut GameRng) -> Vec<UpdateCommand> {
+        self.ai.update(&mut self.state, rng)
     }
 
     fn state(&'a self) -> &'a SpriteState {
         &self.state
     }
 
+    fn state_mut(&'a mut self) -> &'a mut SpriteState {
+        &mut self.state
+    }
+
     fn modify_hp(&mut self, hp_mod: i16) {
         self.state.hp = std::cmp::max(self.state.hp as i16 + hp_mod, 0) as u16;
     }
+
+    fn points(&self) -> u32 {
+        self.points
+    }
+
+    fn is_boss(&self) -> bool {
+        true
+    }
 }
@@ -3,7 +3,7 @@ use crate::board::UpdateCommand;
 use crate::sprite::Cell;
 use crate::sprite::Sprite;
 use crate::sprite::SpriteState;
-use crate::util::{Direction, Point};
+use crate::util::{Direction, GameRng, Point};
 use tui::style::Color;
 
 pub struct Bullet {
@@ -19,7 +19,7 @@ impl Bullet {
                 hp: 1,
                 pos: Point::new(x, y),
                 direction: dir,
-                cells: vec![vec![Cell::new("*", color)]],
+                cells: vec![vec![Cell::new('*', color)]],
             },
             ai: BulletAi::new(bullet_speed),
         }
@@ -27,7 +27,7 @@ impl Bullet {
 }
 
 impl<'a> Sprite<'a> for Bullet {
-    fn update(&mut self) -> Vec<UpdateCommand> {
+    fn update(&mut self, _: &mut GameRng) -> Vec<UpdateCommand> {
         self.ai.update(&mut self.state)
     }
 
@@ -35,6 +35,10 @@ impl<'a> Sprite<'a> for Bullet {
         &self.state
     }
 
+    fn state_mut(&'a mut self) -> &'a mut SpriteState {
+        &mut self.state
+    }
+
     fn set_id(&mut self, id: u32) {
         self.state.id = id;
     }
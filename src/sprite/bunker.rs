@@ -0,0 +1,46 @@
+use crate::board::UpdateCommand;
+use crate::sprite::Cell;
+use crate::sprite::Sprite;
+use crate::sprite::SpriteState;
+use crate::util::{Direction, GameRng, Point};
+
+/// A static shield that loses a cell for every bullet it stops.
+pub struct Bunker {
+    pub state: SpriteState,
+}
+
+impl Bunker {
+    pub fn new(x: i16, y: i16, cells: Vec<Vec<Cell>>) -> Self {
+        Bunker {
+            state: SpriteState {
+                id: 0,
+                hp: 1,
+                pos: Point::new(x, y),
+                direction: Direction::None,
+                cells,
+            },
+        }
+    }
+}
+
+impl<'a> Sprite<'a> for Bunker {
+    fn set_id(&mut self, id: u32) {
+        self.state.id = id;
+    }
+
+    fn update(&mut self, _: &mut GameRng) -> Vec<UpdateCommand> {
+        Vec::new()
+    }
+
+    fn state(&'a self) -> &'a SpriteState {
+        &self.state
+    }
+
+    fn state_mut(&'a mut self) -> &'a mut SpriteState {
+        &mut self.state
+    }
+
+    fn erode(&mut self, p: &Point) -> bool {
+        self.state.erode(p)
+    }
+}
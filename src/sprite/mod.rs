@@ -1,9 +1,10 @@
+#[allow(clippy::module_inception)]
 mod sprite;
 
 pub use sprite::Sprite;
 pub use sprite::SpriteState;
 
-use sprite::Cell;
+pub use sprite::Cell;
 
 mod player;
 
@@ -20,3 +21,11 @@ pub use bullet::Bullet;
 mod boss;
 
 pub use boss::Boss;
+
+mod bunker;
+
+pub use bunker::Bunker;
+
+mod asset;
+
+pub use asset::{AssetError, Assets};
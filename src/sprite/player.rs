@@ -3,37 +3,49 @@ use crate::board::UpdateCommand;
 use crate::sprite::Cell;
 use crate::sprite::Sprite;
 use crate::sprite::SpriteState;
-use crate::util::{Direction, Point};
-use tui::style::Color;
+use crate::util::{Direction, GameRng, Point};
 
+/// Number of ticks the blink animation stays in one phase while invulnerable.
+const BLINK_TICKS: u16 = 8;
+
+/// The player ship. `state.hp` holds the remaining lives.
 pub struct Player {
     pub state: SpriteState,
     pub ai: PlayerAi,
+    pub invulnerable_ticks: u16,
 }
 
 impl Player {
-    pub fn new(x: i16, y: i16) -> Self {
+    pub fn new(x: i16, y: i16, lives: u16, cells: Vec<Vec<Cell>>) -> Self {
         Player {
             state: SpriteState {
-                hp: 1,
+                hp: lives,
                 id: 0,
                 pos: Point::new(x, y),
                 direction: Direction::Up,
-                cells: vec![
-                    vec![
-                        Cell::new(" ", Color::Blue),
-                        Cell::new("^", Color::LightBlue),
-                    ],
-                    vec![
-                        Cell::new("/", Color::Blue),
-                        Cell::new("V", Color::Red),
-                        Cell::new("\\", Color::Blue),
-                    ],
-                ],
+                cells,
             },
             ai: PlayerAi::new(),
+            invulnerable_ticks: 0,
         }
     }
+
+    /// Moves the ship back to `pos`, dropping pending input, and ignores hits
+    /// for the next `invulnerable_ticks` ticks.
+    pub fn respawn(&mut self, pos: Point, invulnerable_ticks: u16) {
+        self.state.pos = pos;
+        self.ai = PlayerAi::new();
+        self.invulnerable_ticks = invulnerable_ticks;
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_ticks > 0
+    }
+
+    /// The ship blinks while invulnerable.
+    pub fn is_visible(&self) -> bool {
+        (self.invulnerable_ticks / BLINK_TICKS).is_multiple_of(2)
+    }
 }
 
 impl<'a> Sprite<'a> for Player {
@@ -41,11 +53,23 @@ impl<'a> Sprite<'a> for Player {
         self.state.id = id;
     }
 
-    fn update(&mut self) -> Vec<UpdateCommand> {
+    fn update(&mut self, _: &mut GameRng) -> Vec<UpdateCommand> {
         self.ai.update(&mut self.state)
     }
 
     fn state(&'a self) -> &'a SpriteState {
         &self.state
     }
+
+    fn state_mut(&'a mut self) -> &'a mut SpriteState {
+        &mut self.state
+    }
+
+    fn collides(&'a self, p: &Point) -> bool {
+        !self.is_invulnerable() && self.state.collides(p)
+    }
+
+    fn modify_hp(&mut self, hp_mod: i16) {
+        self.state.hp = std::cmp::max(self.state.hp as i16 + hp_mod, 0) as u16;
+    }
 }
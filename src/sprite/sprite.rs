@@ -1,18 +1,19 @@
 use crate::board::UpdateCommand;
-use crate::renderer::Renderable;
 use crate::sprite::Bullet;
-use crate::util::{Direction, Point};
+use crate::surface::{Renderable, Surface};
+use crate::util::{Direction, GameRng, Point};
 
 use tui::style::Color;
-use tui::widgets::canvas::Context;
 
 pub trait Sprite<'a> {
-    fn update(&mut self) -> Vec<UpdateCommand>;
+    fn update(&mut self, rng: &mut GameRng) -> Vec<UpdateCommand>;
 
     fn state(&'a self) -> &'a SpriteState;
 
-    fn render(&'a self, ctx: &mut Context) {
-        self.state().render(ctx);
+    fn state_mut(&'a mut self) -> &'a mut SpriteState;
+
+    fn render(&'a self, surface: &mut dyn Surface) {
+        self.state().render(surface);
     }
 
     fn collides(&'a self, p: &Point) -> bool {
@@ -21,17 +22,32 @@ pub trait Sprite<'a> {
 
     fn modify_hp(&mut self, _: i16) {}
 
+    /// Removes the cell hit at `p`, returns false for sprites that can't be eroded.
+    fn erode(&mut self, _: &Point) -> bool {
+        false
+    }
+
+    /// Score awarded for destroying this sprite.
+    fn points(&self) -> u32 {
+        0
+    }
+
+    fn is_boss(&self) -> bool {
+        false
+    }
+
     fn set_id(&'a mut self, id: u32);
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct Cell {
-    pub symbol: &'static str,
+    /// The glyph, a space is transparent.
+    pub symbol: char,
     pub color: Color,
 }
 
 impl Cell {
-    pub fn new(symbol: &'static str, color: Color) -> Self {
+    pub fn new(symbol: char, color: Color) -> Self {
         Cell { symbol, color }
     }
 }
@@ -50,18 +66,46 @@ impl SpriteState {
     }
 
     pub fn collides(&self, p: &Point) -> bool {
+        self.cell_index(p).is_some()
+    }
+
+    /// Blanks the cell at `p`, returns whether there was one to remove.
+    pub fn erode(&mut self, p: &Point) -> bool {
+        match self.cell_index(p) {
+            Some((dx, dy)) => {
+                self.cells[dy][dx].symbol = ' ';
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn width(&self) -> i16 {
+        self.cells.iter().map(|row| row.len()).max().unwrap_or(0) as i16
+    }
+
+    /// Y coordinate of the lowest row of cells.
+    pub fn bottom(&self) -> i16 {
+        self.pos.y - (self.cells.len() as i16 - 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().flatten().all(|cell| cell.symbol == ' ')
+    }
+
+    fn cell_index(&self, p: &Point) -> Option<(usize, usize)> {
         for (dy, cell_row) in self.cells.iter().enumerate() {
             for (dx, cell) in cell_row.iter().enumerate() {
-                if cell.symbol == " " {
+                if cell.symbol == ' ' {
                     continue;
                 }
                 let c = self.pos + Point::new(dx as i16, -(dy as i16));
                 if *p == c {
-                    return true;
+                    return Some((dx, dy));
                 }
             }
         }
-        false
+        None
     }
 
     pub fn fire(&self, dir: Direction, color: Color, bullet_speed: i32) -> Bullet {
@@ -83,7 +127,7 @@ impl SpriteState {
         for y in 0..self.cells.len() {
             let row = &self.cells[y];
             for (x, cell) in row.iter().enumerate() {
-                if cell.symbol == c.to_string() {
+                if cell.symbol == c {
                     v.push(Point::new(x as i16, y as i16));
                 }
             }
@@ -93,19 +137,96 @@ impl SpriteState {
 }
 
 impl Renderable for SpriteState {
-    fn render(&self, ctx: &mut Context) {
+    fn render(&self, surface: &mut dyn Surface) {
         for (dy, cell_row) in self.cells.iter().enumerate() {
             for (dx, cell) in cell_row.iter().enumerate() {
-                if cell.symbol == " " {
+                if cell.symbol == ' ' {
                     continue;
                 }
-                ctx.print(
-                    (self.pos.x + dx as i16) as f64,
-                    (self.pos.y - dy as i16) as f64,
-                    cell.symbol,
+                surface.draw_glyph(
+                    self.pos.x + dx as i16,
+                    self.pos.y - dy as i16,
+                    cell.symbol.encode_utf8(&mut [0; 4]),
                     cell.color,
+                    Color::Reset,
                 );
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::asset::parse_sprite;
+
+    /// Builds a sprite from glyph rows, every visible glyph colored white.
+    fn sprite_at(x: i16, y: i16, glyphs: &str) -> SpriteState {
+        let colors = glyphs
+            .lines()
+            .map(|row| row.replace(|c| c != ' ', "W"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let art = format!("{}\n---\n{}", glyphs, colors);
+        SpriteState {
+            hp: 1,
+            id: 0,
+            pos: Point::new(x, y),
+            direction: Direction::Down,
+            cells: parse_sprite("test.spr", &art).unwrap(),
+        }
+    }
+
+    #[test]
+    fn collides_with_every_row_of_the_grid() {
+        let sprite = sprite_at(10, 20, "/-\\\n|o|\n\\_/");
+
+        assert!(sprite.collides(&Point::new(10, 20)));
+        assert!(sprite.collides(&Point::new(12, 20)));
+        assert!(sprite.collides(&Point::new(11, 19)));
+        assert!(sprite.collides(&Point::new(10, 18)));
+        assert!(sprite.collides(&Point::new(12, 18)));
+    }
+
+    #[test]
+    fn does_not_collide_outside_the_grid() {
+        let sprite = sprite_at(10, 20, "/-\\\n|o|\n\\_/");
+
+        assert!(!sprite.collides(&Point::new(9, 20)));
+        assert!(!sprite.collides(&Point::new(13, 19)));
+        assert!(!sprite.collides(&Point::new(11, 21)));
+        assert!(!sprite.collides(&Point::new(11, 17)));
+    }
+
+    #[test]
+    fn transparent_and_padded_cells_do_not_collide() {
+        let sprite = sprite_at(0, 5, "# #\n#");
+
+        assert!(sprite.collides(&Point::new(0, 5)));
+        assert!(!sprite.collides(&Point::new(1, 5)));
+        assert!(sprite.collides(&Point::new(2, 5)));
+        assert!(sprite.collides(&Point::new(0, 4)));
+        assert!(!sprite.collides(&Point::new(1, 4)));
+        assert!(!sprite.collides(&Point::new(2, 4)));
+    }
+
+    #[test]
+    fn eroded_cells_stop_colliding() {
+        let mut sprite = sprite_at(0, 1, "##\n##");
+
+        assert!(sprite.erode(&Point::new(1, 0)));
+        assert!(!sprite.collides(&Point::new(1, 0)));
+        assert!(!sprite.erode(&Point::new(1, 0)));
+        assert!(!sprite.is_empty());
+    }
+
+    #[test]
+    fn find_char_pos_is_relative_to_the_top_left_cell() {
+        let sprite = sprite_at(40, 25, "+--+\nV  V");
+
+        assert_eq!(
+            sprite.find_char_pos('V'),
+            vec![Point::new(0, 1), Point::new(3, 1)]
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use crate::bindings::{Action, Bindings};
+
+use tui::style::Color;
+
+/// Screens of the game. Each state handles its own input and rendering in
+/// `Rustivanders`, the board is only updated while `Playing`.
+pub enum GameState {
+    Title,
+    Playing,
+    Paused,
+    LevelComplete,
+    GameOver,
+    /// Initials typed so far for a new high-score entry.
+    EnterInitials(String),
+    /// Rank of the entry to highlight, if one was just added.
+    HighScores(Option<usize>),
+    /// Lists the current key bindings.
+    Bindings,
+}
+
+impl GameState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameState::Title => "title",
+            GameState::Playing => "playing",
+            GameState::Paused => "paused",
+            GameState::LevelComplete => "level complete",
+            GameState::GameOver => "game over",
+            GameState::EnterInitials(_) => "enter initials",
+            GameState::HighScores(_) => "high scores",
+            GameState::Bindings => "bindings",
+        }
+    }
+
+    /// Banner shown over the board in states that keep it visible, naming
+    /// the key that moves on.
+    pub fn banner(&self, bindings: &Bindings) -> Vec<(String, Color)> {
+        let (title, prompt, colors) = match self {
+            GameState::Paused => (
+                "PAUSED",
+                format!("Press {} to resume", bindings.prompt(Action::Pause)),
+                (Color::Yellow, Color::LightYellow),
+            ),
+            GameState::LevelComplete => (
+                "You won!",
+                format!(
+                    "Press {} for next level",
+                    bindings.prompt(Action::NextLevel)
+                ),
+                (Color::Green, Color::LightGreen),
+            ),
+            GameState::GameOver => (
+                "GAME OVER",
+                format!("Press {} to continue", bindings.prompt(Action::NextLevel)),
+                (Color::Red, Color::LightRed),
+            ),
+            _ => return Vec::new(),
+        };
+        vec![(title.to_string(), colors.0), (prompt, colors.1)]
+    }
+}
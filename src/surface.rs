@@ -0,0 +1,210 @@
+use crate::level::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::util::Point;
+
+use std::fmt;
+use tui::style::Color;
+
+/// Something that can draw itself onto any `Surface`.
+pub trait Renderable {
+    fn render(&self, surface: &mut dyn Surface);
+}
+
+/// Backend independent drawing target for a frame of the playfield.
+///
+/// Coordinates are playfield positions as used by the game: `x` grows to the
+/// right from 0 to `SCREEN_WIDTH`, `y` grows upwards from 0 to
+/// `SCREEN_HEIGHT`. Anything outside is clipped.
+pub trait Surface {
+    /// Draws a single glyph at `x`, `y`. `Color::Reset` keeps the default
+    /// background.
+    fn draw_glyph(&mut self, x: i16, y: i16, glyph: &str, fg: Color, bg: Color);
+
+    /// Draws `text` left to right starting at `x`, `y`, one glyph per cell.
+    fn draw_text(&mut self, x: i16, y: i16, text: &str, fg: Color);
+}
+
+/// Maps a playfield position onto the cell of a `width` x `height` grid
+/// showing it, `None` when the position is off screen.
+pub fn cell_position(x: i16, y: i16, width: u16, height: u16) -> Option<(u16, u16)> {
+    let (screen_width, screen_height) = (SCREEN_WIDTH as f64, SCREEN_HEIGHT as f64);
+    let (x, y) = (x as f64, y as f64);
+    if width == 0 || height == 0 || x < 0.0 || x > screen_width || y < 0.0 || y > screen_height {
+        return None;
+    }
+    let col = x * (width - 1) as f64 / screen_width;
+    let row = (screen_height - y) * (height - 1) as f64 / screen_height;
+    Some((col as u16, row as u16))
+}
+
+/// The inverse of `cell_position`: the playfield position shown in cell
+/// `col`, `row` of a `width` x `height` grid, `None` outside the grid.
+/// Cells covering several positions map to the leftmost and lowest one.
+pub fn playfield_position(col: u16, row: u16, width: u16, height: u16) -> Option<Point> {
+    if col >= width || row >= height {
+        return None;
+    }
+    let ceil_div = |a: u32, b: u32| if b == 0 { 0 } else { a.div_ceil(b) };
+    let (screen_width, screen_height) = (SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32);
+    let x = ceil_div(col as u32 * screen_width, width as u32 - 1).min(screen_width);
+    let y =
+        screen_height - ceil_div(row as u32 * screen_height, height as u32 - 1).min(screen_height);
+    Some(Point::new(x as i16, y as i16))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameCell {
+    pub symbol: String,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Default for FrameCell {
+    fn default() -> Self {
+        FrameCell {
+            symbol: " ".to_string(),
+            fg: Color::Reset,
+            bg: Color::Reset,
+        }
+    }
+}
+
+/// A plain in-memory grid of cells, e.g. to turn frames into strings for
+/// tests or recordings without a terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameBuffer {
+    pub width: u16,
+    pub height: u16,
+    cells: Vec<FrameCell>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        FrameBuffer {
+            width,
+            height,
+            cells: vec![FrameCell::default(); width as usize * height as usize],
+        }
+    }
+
+    /// A buffer with one cell per playfield column and row.
+    pub fn playfield() -> Self {
+        FrameBuffer::new(SCREEN_WIDTH as u16, SCREEN_HEIGHT as u16)
+    }
+
+    /// Draws `renderable` onto a fresh playfield sized buffer.
+    pub fn render(renderable: &dyn Renderable) -> Self {
+        let mut frame = FrameBuffer::playfield();
+        renderable.render(&mut frame);
+        frame
+    }
+
+    pub fn get(&self, col: u16, row: u16) -> Option<&FrameCell> {
+        if col >= self.width || row >= self.height {
+            return None;
+        }
+        self.cells
+            .get(row as usize * self.width as usize + col as usize)
+    }
+
+    fn set(&mut self, col: u16, row: u16, symbol: &str, fg: Color, bg: Color) {
+        let idx = row as usize * self.width as usize + col as usize;
+        self.cells[idx] = FrameCell {
+            symbol: symbol.to_string(),
+            fg,
+            bg,
+        };
+    }
+}
+
+impl Surface for FrameBuffer {
+    fn draw_glyph(&mut self, x: i16, y: i16, glyph: &str, fg: Color, bg: Color) {
+        if let Some((col, row)) = cell_position(x, y, self.width, self.height) {
+            self.set(col, row, glyph, fg, bg);
+        }
+    }
+
+    fn draw_text(&mut self, x: i16, y: i16, text: &str, fg: Color) {
+        if let Some((col, row)) = cell_position(x, y, self.width, self.height) {
+            let mut buf = [0; 4];
+            for (i, c) in text.chars().take((self.width - col) as usize).enumerate() {
+                self.set(
+                    col + i as u16,
+                    row,
+                    c.encode_utf8(&mut buf),
+                    fg,
+                    Color::Reset,
+                );
+            }
+        }
+    }
+}
+
+/// The symbols of the frame, one line per row without trailing blanks.
+impl fmt::Display for FrameBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1) as usize) {
+            let line = row
+                .iter()
+                .map(|cell| cell.symbol.as_str())
+                .collect::<String>();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_position_flips_y_and_clips() {
+        let (w, h) = (SCREEN_WIDTH as u16, SCREEN_HEIGHT as u16);
+
+        assert_eq!(cell_position(0, SCREEN_HEIGHT as i16, w, h), Some((0, 0)));
+        assert_eq!(cell_position(0, 0, w, h), Some((0, h - 1)));
+        assert_eq!(
+            cell_position(SCREEN_WIDTH as i16, 0, w, h),
+            Some((w - 1, h - 1))
+        );
+        assert_eq!(cell_position(-1, 5, w, h), None);
+        assert_eq!(cell_position(5, SCREEN_HEIGHT as i16 + 1, w, h), None);
+        assert_eq!(cell_position(5, 5, 0, 0), None);
+    }
+
+    #[test]
+    fn playfield_position_inverts_cell_position() {
+        let (w, h) = (SCREEN_WIDTH as u16, SCREEN_HEIGHT as u16);
+
+        for row in 0..h {
+            for col in 0..w {
+                let pos = playfield_position(col, row, w, h).unwrap();
+                assert_eq!(cell_position(pos.x, pos.y, w, h), Some((col, row)));
+            }
+        }
+        assert_eq!(playfield_position(0, 0, w, h), Some(Point::new(0, 30)));
+        assert_eq!(playfield_position(w, 0, w, h), None);
+        assert_eq!(playfield_position(0, h, w, h), None);
+    }
+
+    #[test]
+    fn glyphs_keep_their_colors() {
+        let mut frame = FrameBuffer::playfield();
+
+        frame.draw_glyph(10, SCREEN_HEIGHT as i16, "#", Color::Red, Color::Blue);
+
+        let cell = frame.get(9, 0).unwrap();
+        assert_eq!(cell.symbol, "#");
+        assert_eq!(cell.fg, Color::Red);
+        assert_eq!(cell.bg, Color::Blue);
+    }
+
+    #[test]
+    fn text_is_clipped_at_the_right_edge() {
+        let mut frame = FrameBuffer::new(10, 2);
+
+        frame.draw_text(SCREEN_WIDTH as i16 - 10, 0, "clipped text", Color::White);
+
+        assert_eq!(frame.to_string(), "\n        cl\n");
+    }
+}
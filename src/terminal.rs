@@ -0,0 +1,70 @@
+//! Terminal setup for the backend chosen at build time, termion by default
+//! or crossterm with the `crossterm` feature. Either way the terminal is put
+//! into raw mode on the alternate screen with mouse reporting, and restored
+//! when the backend is dropped. That includes unwinding from a panic, so the
+//! backend is the only place to change terminal modes.
+
+#[cfg(not(feature = "crossterm"))]
+mod imp {
+    use std::io::{self, Stdout};
+    use termion::input::MouseTerminal;
+    use termion::raw::{IntoRawMode, RawTerminal};
+    use termion::screen::AlternateScreen;
+    use tui::backend::TermionBackend;
+
+    pub type TermBackend = TermionBackend<AlternateScreen<MouseTerminal<RawTerminal<Stdout>>>>;
+
+    pub fn backend() -> io::Result<TermBackend> {
+        let stdout = MouseTerminal::from(io::stdout().into_raw_mode()?);
+        Ok(TermionBackend::new(AlternateScreen::from(stdout)))
+    }
+}
+
+#[cfg(feature = "crossterm")]
+mod imp {
+    use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+    use crossterm::execute;
+    use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+    use std::io::{self, Stdout, Write};
+    use tui::backend::CrosstermBackend;
+
+    pub type TermBackend = CrosstermBackend<Screen>;
+
+    pub fn backend() -> io::Result<TermBackend> {
+        Ok(CrosstermBackend::new(Screen::enter()?))
+    }
+
+    /// Stdout in raw mode on the alternate screen with mouse capture, undone
+    /// on drop the way termion's wrappers do it.
+    pub struct Screen {
+        stdout: Stdout,
+    }
+
+    impl Screen {
+        fn enter() -> io::Result<Screen> {
+            let mut stdout = io::stdout();
+            terminal::enable_raw_mode()?;
+            execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+            Ok(Screen { stdout })
+        }
+    }
+
+    impl Write for Screen {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.stdout.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.stdout.flush()
+        }
+    }
+
+    impl Drop for Screen {
+        fn drop(&mut self) {
+            let _ = execute!(self.stdout, DisableMouseCapture, LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+}
+
+pub use imp::{backend, TermBackend};
@@ -1,5 +1,11 @@
 use std::ops::{Add, AddAssign, Sub};
 
+/// The random number generator behind all gameplay randomness. It is seeded
+/// explicitly so that runs can be reproduced. Unlike `StdRng`, whose
+/// algorithm may change with a rand release, ChaCha8 is pinned by its crate
+/// so recorded seeds keep playing out the same.
+pub type GameRng = rand_chacha::ChaCha8Rng;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Point {
     pub x: i16,